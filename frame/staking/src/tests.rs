@@ -460,8 +460,8 @@ fn nominating_and_rewards_should_work() {
 					total: 1000 + 800,
 					own: 1000,
 					others: vec![
-						IndividualExposure { who: 3, value: 400 },
 						IndividualExposure { who: 1, value: 400 },
+						IndividualExposure { who: 3, value: 400 },
 					]
 				},
 			);
@@ -471,8 +471,8 @@ fn nominating_and_rewards_should_work() {
 					total: 1000 + 1200,
 					own: 1000,
 					others: vec![
-						IndividualExposure { who: 3, value: 600 },
 						IndividualExposure { who: 1, value: 600 },
+						IndividualExposure { who: 3, value: 600 },
 					]
 				},
 			);
@@ -0,0 +1,251 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sensitivity analysis of election results.
+//!
+//! The functions of this module answer questions like "how stable is this result against a small
+//! change in the input". They work by re-running [`seq_phragmen`] on modified inputs and checking
+//! whether the set of winners changes. This is expensive and not meant to be used on-chain.
+
+use crate::{
+	IdentifierT, ElectionResult, ExtendedBalance, VoteWeight, seq_phragmen, to_without_backing,
+	internal, approval_ranking,
+};
+use sp_arithmetic::{PerThing, InnerOf, Perbill};
+use sp_std::{prelude::*, vec, collections::btree_map::BTreeMap};
+
+/// Run seq-phragmen without balancing and return the sorted set of winners.
+fn winner_set<AccountId: IdentifierT>(
+	to_elect: usize,
+	candidates: Vec<AccountId>,
+	voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+) -> Result<Vec<AccountId>, &'static str> {
+	let ElectionResult { winners, .. } = seq_phragmen::<AccountId, Perbill>(
+		to_elect,
+		candidates,
+		voters,
+		None,
+	)?;
+	let mut winners = to_without_backing(winners);
+	internal::stable_sort_by_id(&mut winners, |who| who);
+	Ok(winners)
+}
+
+/// Estimate how much the weight of each edge of `result` could decrease before the set of winners
+/// changes.
+///
+/// The returned map is keyed by `(voter, target)`. A decrease of the weight of an edge is modeled
+/// as the voter withdrawing that amount from its stake, after which seq-phragmen is executed again
+/// with all the other inputs intact. The largest such decrease that keeps the winners intact is
+/// found by binary search over `[0, weight]`.
+///
+/// This is an approximation: the winner set is not strictly monotonic in the stake of one voter,
+/// and the algorithm might re-distribute the remaining stake of the voter differently. Hence, the
+/// returned value should be interpreted as an indicator of how fragile an edge is, rather than an
+/// exact bound. Edges of `result` whose voter is not in `voters` are ignored.
+///
+/// This executes `O(E ⋅ log(w))` elections, where `w` is the maximum edge weight.
+pub fn edge_slack<AccountId: IdentifierT, P>(
+	result: &ElectionResult<AccountId, P>,
+	candidates: Vec<AccountId>,
+	voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+) -> Result<BTreeMap<(AccountId, AccountId), ExtendedBalance>, &'static str>
+where
+	ExtendedBalance: From<InnerOf<P>>,
+	P: PerThing + sp_std::ops::Mul<ExtendedBalance, Output = ExtendedBalance>,
+{
+	let to_elect = result.winners.len();
+	let original = winner_set(to_elect, candidates.clone(), voters.clone())?;
+	let mut slacks = BTreeMap::new();
+
+	for assignment in result.assignments.iter() {
+		let voter_index = match voters.iter().position(|(who, _, _)| *who == assignment.who) {
+			Some(index) => index,
+			None => continue,
+		};
+		let stake = voters[voter_index].1;
+		let staked = assignment.clone().into_staked(stake.into());
+
+		for (target, weight) in staked.distribution {
+			// the stake of a voter is a `VoteWeight`, so is any portion of it.
+			let weight = weight.min(stake.into()) as VoteWeight;
+			let is_intact = |decrease: VoteWeight| -> Result<bool, &'static str> {
+				let mut decreased = voters.clone();
+				decreased[voter_index].1 = stake.saturating_sub(decrease);
+				Ok(winner_set(to_elect, candidates.clone(), decreased)? == original)
+			};
+
+			// invariant: `low` keeps the winners intact.
+			let (mut low, mut high) = (0, weight);
+			while low < high {
+				let mid = high - (high - low) / 2;
+				if is_intact(mid)? {
+					low = mid;
+				} else {
+					high = mid - 1;
+				}
+			}
+
+			slacks.insert((assignment.who.clone(), target), low.into());
+		}
+	}
+
+	Ok(slacks)
+}
+
+/// Find the cheapest single vote flip that changes the set of winners of `result`, if any.
+///
+/// A flip means that a voter replaces one of its targets that is a winner with a candidate that
+/// is not. The voters are tried in ascending order of stake, and the first flip that changes the
+/// winners is returned as `(voter, from, to)`. If no such flip exists, `None` is returned and the
+/// result is considered robust against any single voter.
+///
+/// This runs an entire election for each possible flip, i.e. `O(V ⋅ k ⋅ C)` elections in the
+/// worst case.
+pub fn min_voter_flip_to_change_winners<AccountId: IdentifierT, P: PerThing>(
+	result: &ElectionResult<AccountId, P>,
+	candidates: Vec<AccountId>,
+	voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+) -> Result<Option<(AccountId, AccountId, AccountId)>, &'static str> {
+	let to_elect = result.winners.len();
+	let original = winner_set(to_elect, candidates.clone(), voters.clone())?;
+	let is_winner = |who: &AccountId| result.winners.iter().any(|(w, _)| w == who);
+
+	let mut by_stake = voters
+		.iter()
+		.map(|(who, stake, _)| (who.clone(), *stake))
+		.enumerate()
+		.collect::<Vec<_>>();
+	internal::stable_sort_by_key_then_id(&mut by_stake, |(_, (_, s))| *s, |(_, (who, _))| who);
+
+	for (voter_index, _) in by_stake {
+		let (who, _, targets) = &voters[voter_index];
+		for (target_index, from) in targets.iter().enumerate().filter(|(_, t)| is_winner(t)) {
+			for to in candidates.iter().filter(|c| !is_winner(c) && !targets.contains(c)) {
+				let mut flipped = voters.clone();
+				flipped[voter_index].2[target_index] = to.clone();
+				if winner_set(to_elect, candidates.clone(), flipped)? != original {
+					return Ok(Some((who.clone(), from.clone(), to.clone())))
+				}
+			}
+		}
+	}
+
+	Ok(None)
+}
+
+/// Find all the kingmaker voters of `result`, namely the voters whose removal from the election
+/// alone changes the set of winners.
+///
+/// Each voter is removed in turn, with all the other inputs intact, and the winners of the
+/// remaining election are compared against those of the original one. The returned voters are
+/// sorted by id. This is exact, but runs an entire election per voter, i.e. `O(V)` elections.
+pub fn kingmaker_voters<AccountId: IdentifierT, P: PerThing>(
+	result: &ElectionResult<AccountId, P>,
+	candidates: Vec<AccountId>,
+	voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+) -> Result<Vec<AccountId>, &'static str> {
+	let to_elect = result.winners.len();
+	let original = winner_set(to_elect, candidates.clone(), voters.clone())?;
+
+	let mut kingmakers = Vec::new();
+	for voter_index in 0..voters.len() {
+		let mut without = voters.clone();
+		let (who, _, _) = without.remove(voter_index);
+		if winner_set(to_elect, candidates.clone(), without)? != original {
+			kingmakers.push(who);
+		}
+	}
+
+	internal::stable_sort_by_id(&mut kingmakers, |who| who);
+	Ok(kingmakers)
+}
+
+/// Compute the additional stake that the strongest loser of `result` needs, in order to unseat
+/// `target`.
+///
+/// The strongest loser is the candidate with the highest approval stake that is not a winner, see
+/// [`approval_ranking`]. The additional stake is modeled as a self vote of the loser: if it is
+/// already a voter, its stake is increased and it votes for itself as well. The smallest
+/// additional stake after which `target` is no longer a winner is found by an exponential search,
+/// followed by a binary search. Similar to [`edge_slack`], this is an approximation, since the
+/// winner set is not strictly monotonic in the stake of one voter.
+///
+/// Returns `None` if `target` is not a winner, if there is no loser, or if no amount of stake
+/// behind the strongest loser can unseat `target`. Note that the latter is common for a dominant
+/// winner, since electing the loser mostly displaces the weakest of the winners.
+///
+/// This executes `O(log(s))` elections, where `s` is the returned stake.
+pub fn stake_to_unseat<AccountId: IdentifierT, P: PerThing>(
+	target: &AccountId,
+	result: &ElectionResult<AccountId, P>,
+	candidates: Vec<AccountId>,
+	voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+) -> Result<Option<ExtendedBalance>, &'static str> {
+	let to_elect = result.winners.len();
+	let is_winner = |who: &AccountId| result.winners.iter().any(|(w, _)| w == who);
+	if !is_winner(target) {
+		return Ok(None);
+	}
+	let loser = match approval_ranking(candidates.clone(), voters.clone())
+		.into_iter()
+		.map(|(who, _)| who)
+		.find(|who| !is_winner(who))
+	{
+		Some(loser) => loser,
+		None => return Ok(None),
+	};
+
+	let is_unseated = |extra: VoteWeight| -> Result<bool, &'static str> {
+		let mut boosted = voters.clone();
+		match boosted.iter_mut().find(|(who, _, _)| *who == loser) {
+			Some((_, stake, targets)) => {
+				*stake = stake.saturating_add(extra);
+				if !targets.contains(&loser) {
+					targets.push(loser.clone());
+				}
+			},
+			None => boosted.push((loser.clone(), extra, vec![loser.clone()])),
+		}
+		Ok(!winner_set(to_elect, candidates.clone(), boosted)?.contains(target))
+	};
+
+	// the target might be unseated already, then the search below would never test zero.
+	if is_unseated(0)? {
+		return Ok(Some(0));
+	}
+
+	// invariant: `low` does not unseat the target, `high` does.
+	let mut high: VoteWeight = 1;
+	while !is_unseated(high)? {
+		if high == VoteWeight::MAX {
+			return Ok(None);
+		}
+		high = high.saturating_mul(2);
+	}
+	let mut low = high / 2;
+	while high - low > 1 {
+		let mid = low + (high - low) / 2;
+		if is_unseated(mid)? {
+			high = mid;
+		} else {
+			low = mid;
+		}
+	}
+
+	Ok(Some(high.into()))
+}
@@ -26,7 +26,7 @@
 //!
 //! See [`balance`] for more information.
 
-use crate::{IdentifierT, Voter, ExtendedBalance, Edge};
+use crate::{IdentifierT, Voter, ExtendedBalance, Edge, internal};
 use sp_arithmetic::traits::Zero;
 use sp_std::prelude::*;
 
@@ -41,7 +41,7 @@ use sp_std::prelude::*;
 ///
 /// Note that some reference implementation adopt an approach in which voters are balanced randomly
 /// per round. To advocate determinism, we don't do this. In each round, all voters are exactly
-/// balanced once, in the same order.
+/// balanced once, in the same order. See [`balance_shuffled`] for a seeded alternative.
 ///
 /// Also, note that due to re-distribution of weights, the outcome of this function might contain
 /// edges with weight zero. The call site should filter such weight if desirable. Moreover, the
@@ -56,6 +56,24 @@ pub fn balance<AccountId: IdentifierT>(
 	voters: &mut Vec<Voter<AccountId>>,
 	iterations: usize,
 	tolerance: ExtendedBalance,
+) -> usize {
+	balance_bounded(voters, iterations, tolerance, usize::MAX)
+}
+
+/// Same as [`balance`], but the stake of each voter is split among at most `max_inner_iterations`
+/// of its elected edges, namely those with the least backed stake.
+///
+/// Balancing a voter iterates over its elected edges to find how many of them its stake should be
+/// split among. For adversarial inputs in which voters have many edges, this bounds the work done
+/// per voter, hence the worst case execution of each round. The outcome is still a valid (and
+/// normalized) distribution, yet it might be less balanced than that of [`balance`]. A value of
+/// zero is treated as one. If `max_inner_iterations` is not less than the number of edges of any
+/// voter, the outcome is exactly the same as that of [`balance`].
+pub fn balance_bounded<AccountId: IdentifierT>(
+	voters: &mut [Voter<AccountId>],
+	iterations: usize,
+	tolerance: ExtendedBalance,
+	max_inner_iterations: usize,
 ) -> usize {
 	if iterations == 0 { return 0; }
 
@@ -63,7 +81,55 @@ pub fn balance<AccountId: IdentifierT>(
 	loop {
 		let mut max_diff = 0;
 		for voter in voters.iter_mut() {
-			let diff = balance_voter(voter, tolerance);
+			let diff = balance_voter(voter, tolerance, max_inner_iterations);
+			if diff > max_diff { max_diff = diff; }
+		}
+
+		iter += 1;
+		if max_diff <= tolerance || iter >= iterations {
+			break iter;
+		}
+	}
+}
+
+/// Same as [`balance`], but in each round, the voters are balanced in a shuffled order that is
+/// derived from `seed`.
+///
+/// The order only depends on the seed and the ids of the voters, not on the order of `voters`.
+/// Hence, given the same seed, the outcome is the same regardless of how the voters are ordered
+/// in storage, which allows multiple nodes to reproduce the exact same balanced solution. The
+/// order of `voters` itself is not changed.
+pub fn balance_shuffled<AccountId: IdentifierT>(
+	voters: &mut [Voter<AccountId>],
+	iterations: usize,
+	tolerance: ExtendedBalance,
+	seed: u64,
+) -> usize {
+	balance_with_source(voters, iterations, tolerance, &mut internal::SplitMix64::new(seed))
+}
+
+/// Same as [`balance_shuffled`], with the given source of random draws.
+pub(crate) fn balance_with_source<AccountId: IdentifierT, R: internal::RandomSource>(
+	voters: &mut [Voter<AccountId>],
+	iterations: usize,
+	tolerance: ExtendedBalance,
+	rng: &mut R,
+) -> usize {
+	if iterations == 0 { return 0; }
+
+	// indices of the voters, sorted by id. The sort is stable.
+	let mut canonical = voters.iter().map(|v| v.who.clone()).enumerate().collect::<Vec<_>>();
+	internal::stable_sort_by_id(&mut canonical, |(_, who)| who);
+	let canonical = canonical.into_iter().map(|(index, _)| index).collect::<Vec<_>>();
+
+	let mut iter = 0;
+	loop {
+		let mut order = canonical.clone();
+		rng.shuffle(&mut order);
+
+		let mut max_diff = 0;
+		for index in order {
+			let diff = balance_voter(&mut voters[index], tolerance, usize::MAX);
 			if diff > max_diff { max_diff = diff; }
 		}
 
@@ -75,9 +141,12 @@ pub fn balance<AccountId: IdentifierT>(
 }
 
 /// Internal implementation of balancing for one voter.
+///
+/// The stake is split among at most `max_splits` of the elected edges, see [`balance_bounded`].
 pub(crate) fn balance_voter<AccountId: IdentifierT>(
 	voter: &mut Voter<AccountId>,
 	tolerance: ExtendedBalance,
+	max_splits: usize,
 ) -> ExtendedBalance {
 	// create a shallow copy of the elected ones. The original one will not be used henceforth.
 	let mut elected_edges = voter.edges
@@ -142,9 +211,10 @@ pub(crate) fn balance_voter<AccountId: IdentifierT>(
 	elected_edges.sort_by_key(|e| e.candidate.borrow().backed_stake);
 
 	let mut cumulative_backed_stake = Zero::zero();
-	let mut last_index = elected_edges.len() - 1;
+	let max_splits = max_splits.max(1).min(elected_edges.len());
+	let mut last_index = max_splits - 1;
 
-	for (index, edge) in elected_edges.iter().enumerate() {
+	for (index, edge) in elected_edges.iter().take(max_splits).enumerate() {
 		let index = index as ExtendedBalance;
 		let backed_stake = edge.candidate.borrow().backed_stake;
 		let temp = backed_stake.saturating_mul(index);
@@ -19,9 +19,11 @@
 
 use crate::{
 	Assignment, ExtendedBalance, VoteWeight, IdentifierT, StakedAssignment, WithApprovalOf, Error,
+	ElectionResult, ElectionScore, SupportMap, seq_phragmen, reduce, build_support_map,
+	evaluate_support, internal, Support,
 };
-use sp_arithmetic::{PerThing, InnerOf};
-use sp_std::prelude::*;
+use sp_arithmetic::{PerThing, InnerOf, Perbill, helpers_128bit::multiply_by_rational};
+use sp_std::{prelude::*, vec, collections::btree_map::BTreeMap};
 
 /// Converts a vector of ratio assignments into ones with absolute budget value.
 ///
@@ -44,6 +46,59 @@ where
 		.collect()
 }
 
+/// What to do with the edges of a voter whose stake rounds down to zero when converting ratio
+/// assignments into staked ones. See [`assignment_ratio_to_staked_with_policy`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ZeroEdgePolicy {
+	/// Drop the zero edges and keep the rest as-is. A voter with only dust edges vanishes.
+	Drop,
+	/// Same as [`ZeroEdgePolicy::Drop`], except that if all of the edges of a voter are zero, the
+	/// whole stake of the voter is concentrated on its highest-ratio target, so that its voice is
+	/// not lost.
+	Concentrate,
+}
+
+/// Same as [`assignment_ratio_to_staked`], with edges that round down to zero treated according
+/// to `policy`.
+///
+/// With [`ZeroEdgePolicy::Concentrate`], ties among the highest-ratio targets are broken in favour
+/// of the one that comes first in the distribution.
+///
+/// Note that this will NOT attempt at normalizing the result.
+pub fn assignment_ratio_to_staked_with_policy<A: IdentifierT, P, FS>(
+	ratios: Vec<Assignment<A, P>>,
+	stake_of: FS,
+	policy: ZeroEdgePolicy,
+) -> Vec<StakedAssignment<A>>
+where
+	for<'r> FS: Fn(&'r A) -> VoteWeight,
+	P: PerThing + sp_std::ops::Mul<ExtendedBalance, Output = ExtendedBalance>,
+	ExtendedBalance: From<InnerOf<P>>,
+{
+	ratios
+		.into_iter()
+		.map(|a| {
+			let stake: ExtendedBalance = stake_of(&a.who).into();
+			let top = a
+				.distribution
+				.iter()
+				.fold(None, |top: Option<&(A, P)>, d| match top {
+					Some(t) if t.1 >= d.1 => Some(t),
+					_ => Some(d),
+				})
+				.map(|(target, _)| target.clone());
+			let mut staked = a.into_staked(stake);
+			staked.distribution.retain(|(_, w)| *w != 0);
+			if let (ZeroEdgePolicy::Concentrate, Some(target), true) =
+				(policy, top, staked.distribution.is_empty())
+			{
+				staked.distribution = vec![(target, stake)];
+			}
+			staked
+		})
+		.collect()
+}
+
 /// Same as [`assignment_ratio_to_staked`] and try and do normalization.
 pub fn assignment_ratio_to_staked_normalized<A: IdentifierT, P: PerThing, FS>(
 	ratio: Vec<Assignment<A, P>>,
@@ -92,6 +147,380 @@ pub fn to_without_backing<A: IdentifierT>(winners: Vec<WithApprovalOf<A>>) -> Ve
 	winners.into_iter().map(|(who, _)| who).collect::<Vec<A>>()
 }
 
+/// The merged voters, and the rest of the members of each group keyed by its representative, as
+/// returned by [`merge_dust_voters`].
+pub type MergedVoters<A> = (Vec<(A, VoteWeight, Vec<A>)>, BTreeMap<A, Vec<A>>);
+
+/// Coalesce all voters with a stake less than `dust` who vote for the exact same set of targets
+/// into a single synthetic voter, with the sum of their stakes.
+///
+/// The synthetic voter takes the identifier of the first voter of each group, namely the
+/// _representative_. The returned map contains the rest of the members of each group, keyed by
+/// the representative. Groups with only one member are left as-is and are not present in the map.
+///
+/// Use [`expand_dust_assignments`] to reverse this operation on the election result.
+pub fn merge_dust_voters<A: IdentifierT>(
+	voters: Vec<(A, VoteWeight, Vec<A>)>,
+	dust: VoteWeight,
+) -> MergedVoters<A> {
+	let mut merged: Vec<(A, VoteWeight, Vec<A>)> = Vec::with_capacity(voters.len());
+	let mut members = BTreeMap::<A, Vec<A>>::new();
+	// sorted and de-duplicated targets of a group to the index of its representative in `merged`.
+	let mut groups = BTreeMap::<Vec<A>, usize>::new();
+
+	for (who, stake, targets) in voters {
+		if stake >= dust {
+			merged.push((who, stake, targets));
+			continue;
+		}
+
+		let mut key = targets.clone();
+		internal::stable_sort_by_id(&mut key, |who| who);
+		key.dedup();
+		if let Some(index) = groups.get(&key) {
+			let representative = &mut merged[*index];
+			representative.1 = representative.1.saturating_add(stake);
+			members.entry(representative.0.clone()).or_default().push(who);
+		} else {
+			groups.insert(key, merged.len());
+			merged.push((who, stake, targets));
+		}
+	}
+
+	(merged, members)
+}
+
+/// Expand the assignments of the synthetic voters created by [`merge_dust_voters`] back to all the
+/// members of their group.
+///
+/// Since all members of a group vote for the same targets, the distribution of the representative
+/// is a ratio that equally applies to each member, proportional to their own stake. The expanded
+/// assignments of each group are placed right after the one of the representative.
+pub fn expand_dust_assignments<A: IdentifierT, P: PerThing>(
+	assignments: Vec<Assignment<A, P>>,
+	members: &BTreeMap<A, Vec<A>>,
+) -> Vec<Assignment<A, P>> {
+	let mut expanded = Vec::with_capacity(assignments.len());
+	for assignment in assignments {
+		let others = members.get(&assignment.who).cloned().unwrap_or_default();
+		let distribution = assignment.distribution.clone();
+		expanded.push(assignment);
+		expanded.extend(others.into_iter().map(|who| Assignment {
+			who,
+			distribution: distribution.clone(),
+		}));
+	}
+	expanded
+}
+
+/// The election result, its support map and its score, as returned by [`run_staking_election`].
+pub type StakingElectionOutcome<A, P> = (ElectionResult<A, P>, SupportMap<A>, ElectionScore);
+
+/// Run the full election pipeline that is typically needed in a staking context.
+///
+/// This executes the following steps:
+/// 1. elect `to_elect` members via [`seq_phragmen`], with `balance_iterations` rounds of balancing.
+/// 2. convert the ratio assignments into staked ones, using `stake_of`.
+/// 3. [`reduce`] the staked assignments.
+/// 4. build the support map of the winners, and evaluate its score.
+///
+/// The assignments of the returned election result are the reduced ones, converted back into
+/// normalized ratios. Hence, all the returned values are consistent with one another.
+pub fn run_staking_election<A: IdentifierT, P, FS>(
+	candidates: Vec<A>,
+	voters: Vec<(A, Vec<A>)>,
+	stake_of: FS,
+	to_elect: usize,
+	balance_iterations: usize,
+) -> Result<StakingElectionOutcome<A, P>, Error>
+where
+	for<'r> FS: Fn(&'r A) -> VoteWeight,
+	P: PerThing + sp_std::ops::Mul<ExtendedBalance, Output = ExtendedBalance>,
+	ExtendedBalance: From<InnerOf<P>>,
+{
+	let voters = voters
+		.into_iter()
+		.map(|(who, targets)| {
+			let stake = stake_of(&who);
+			(who, stake, targets)
+		})
+		.collect::<Vec<_>>();
+	let balance = if balance_iterations > 0 { Some((balance_iterations, 0)) } else { None };
+
+	let ElectionResult { winners, assignments } = seq_phragmen::<A, P>(
+		to_elect,
+		candidates,
+		voters,
+		balance,
+	).map_err(Error::ArithmeticError)?;
+
+	let mut staked = assignment_ratio_to_staked_normalized(assignments, &stake_of)?;
+	reduce(&mut staked);
+
+	let winners_only = winners.iter().map(|(who, _)| who.clone()).collect::<Vec<_>>();
+	let support_map = build_support_map(&winners_only, &staked)
+		.map_err(|_| Error::InvalidSupportEdge)?;
+	let score = evaluate_support(&support_map);
+
+	let assignments = assignment_staked_to_ratio_normalized(staked)?;
+	Ok((ElectionResult { winners, assignments }, support_map, score))
+}
+
+/// The unbalanced result, the balanced result and the improvement of the score, as returned by
+/// [`elect_with_and_without_balancing`].
+pub type BalancingComparison<A, P> = (ElectionResult<A, P>, ElectionResult<A, P>, ElectionScore);
+
+/// Elect `to_elect` members via [`seq_phragmen`] twice, once without and once with
+/// `balance_iterations` rounds of balancing, and report how much balancing improved the score.
+///
+/// The returned tuple contains the unbalanced result, the balanced result and the improvement of
+/// the score of the latter over the former. Each element of the improvement is how much better
+/// the balanced score is in that element, i.e. an increase of the first two and a decrease of the
+/// third one, and is zero if it is not better. The scores are evaluated over the support maps of
+/// the winners, using the stakes of the given `voters`.
+pub fn elect_with_and_without_balancing<A: IdentifierT, P>(
+	to_elect: usize,
+	candidates: Vec<A>,
+	voters: Vec<(A, VoteWeight, Vec<A>)>,
+	balance_iterations: usize,
+) -> Result<BalancingComparison<A, P>, Error>
+where
+	P: PerThing + sp_std::ops::Mul<ExtendedBalance, Output = ExtendedBalance>,
+	ExtendedBalance: From<InnerOf<P>>,
+{
+	let stakes = voters
+		.iter()
+		.map(|(who, stake, _)| (who.clone(), *stake))
+		.collect::<BTreeMap<_, _>>();
+	let stake_of = |who: &A| stakes.get(who).cloned().unwrap_or_default();
+	let score_of = |result: &ElectionResult<A, P>| -> Result<ElectionScore, Error> {
+		let staked = assignment_ratio_to_staked_normalized(result.assignments.clone(), stake_of)?;
+		let winners = result.winners.iter().map(|(who, _)| who.clone()).collect::<Vec<_>>();
+		let support_map = build_support_map(&winners, &staked)
+			.map_err(|_| Error::InvalidSupportEdge)?;
+		Ok(evaluate_support(&support_map))
+	};
+
+	let unbalanced =
+		seq_phragmen::<A, P>(to_elect, candidates.clone(), voters.clone(), None)
+			.map_err(Error::ArithmeticError)?;
+	let balance = if balance_iterations > 0 { Some((balance_iterations, 0)) } else { None };
+	let balanced = seq_phragmen::<A, P>(to_elect, candidates, voters, balance)
+		.map_err(Error::ArithmeticError)?;
+
+	let [min_before, sum_before, squared_before] = score_of(&unbalanced)?;
+	let [min_after, sum_after, squared_after] = score_of(&balanced)?;
+	let improvement = [
+		min_after.saturating_sub(min_before),
+		sum_after.saturating_sub(sum_before),
+		squared_before.saturating_sub(squared_after),
+	];
+
+	Ok((unbalanced, balanced, improvement))
+}
+
+/// Report all the mutual backings among `assignments`, namely all the `(voter, target)` pairs
+/// where `target` is itself a voter that backs `voter`.
+///
+/// Edges with a zero stake are ignored. Each mutual backing is reported twice, once from the side
+/// of each account. The returned pairs are sorted.
+///
+/// A mutual backing is a cycle of length two in the directed graph of backings. This is not built
+/// on the cycle detection of the nodes used by [`reduce`]: a node there only stores one parent,
+/// hence that graph can only hold a spanning forest of the undirected edges, and does not keep
+/// their direction. Looking up the reverse of each edge finds exactly the cycles of length two,
+/// in `O(E ⋅ log(E))` where `E` is the number of edges.
+pub fn reciprocity_report<A: IdentifierT>(assignments: &[StakedAssignment<A>]) -> Vec<(A, A)> {
+	let edges = assignments
+		.iter()
+		.flat_map(|a| a.distribution.iter().map(move |d| (a, d)))
+		.filter(|(_, (_, w))| *w > 0)
+		.map(|(a, (target, _))| ((a.who.clone(), target.clone()), ()))
+		.collect::<BTreeMap<_, _>>();
+
+	edges
+		.keys()
+		.filter(|(voter, target)| voter != target)
+		.filter(|(voter, target)| edges.contains_key(&(target.clone(), voter.clone())))
+		.cloned()
+		.collect()
+}
+
+/// Split the backing of `validator` in `support` into its own stake, namely that of its self
+/// vote, and the stake of all the other voters.
+///
+/// If `validator` does not back itself, its own stake is zero. The other voters keep their order
+/// in `support`.
+pub fn split_exposure<A: IdentifierT>(
+	support: &Support<A>,
+	validator: &A,
+) -> (ExtendedBalance, Vec<(A, ExtendedBalance)>) {
+	let mut own: ExtendedBalance = 0;
+	let mut others = Vec::with_capacity(support.voters.len());
+	for (voter, weight) in support.voters.iter() {
+		if voter == validator {
+			own = own.saturating_add(*weight);
+		} else {
+			others.push((voter.clone(), *weight));
+		}
+	}
+	(own, others)
+}
+
+/// Report how the contribution of `voter` to each target has changed from `previous_support` to
+/// `current_support`, e.g. between two consecutive eras.
+///
+/// A contribution to a target that is new in `current_support` is reported as a positive change,
+/// and one that has been dropped is reported as a negative change. Unchanged contributions are
+/// omitted. The returned changes are sorted by target, and saturate at the bounds of `i128`.
+pub fn exposure_change<A: IdentifierT>(
+	previous_support: &SupportMap<A>,
+	current_support: &SupportMap<A>,
+	voter: &A,
+) -> Vec<(A, i128)> {
+	let contributions = |support_map: &SupportMap<A>| {
+		support_map
+			.iter()
+			.map(|(target, support)| {
+				let stake = support
+					.voters
+					.iter()
+					.filter(|(who, _)| who == voter)
+					.fold(0, |acc: ExtendedBalance, (_, w)| acc.saturating_add(*w));
+				(target.clone(), stake)
+			})
+			.filter(|(_, stake)| *stake > 0)
+			.collect::<BTreeMap<_, _>>()
+	};
+	let previous = contributions(previous_support);
+	let current = contributions(current_support);
+
+	let to_signed = |x: ExtendedBalance| x.min(i128::MAX as ExtendedBalance) as i128;
+	let mut targets = previous.keys().chain(current.keys()).cloned().collect::<Vec<_>>();
+	internal::stable_sort_by_id(&mut targets, |target| target);
+	targets.dedup();
+	targets
+		.into_iter()
+		.filter_map(|target| {
+			let before = previous.get(&target).copied().unwrap_or_default();
+			let after = current.get(&target).copied().unwrap_or_default();
+			let change = if after >= before {
+				to_signed(after - before)
+			} else {
+				-to_signed(before - after)
+			};
+			if change == 0 {
+				None
+			} else {
+				Some((target, change))
+			}
+		})
+		.collect()
+}
+
+/// Simulate the effect of slashing `slashed` by `slash_fraction` on `support_map`.
+///
+/// Each backer of `slashed` loses `slash_fraction` of its exposure to `slashed`, which is deducted
+/// from its entry in `backer_stakes`. Since the stake of a backer is bonded as a whole, the slash
+/// cascades: all the edges of that backer in `support_map`, not only the one to `slashed`, are
+/// scaled down by the ratio of its new stake to its old stake, and the totals of all affected
+/// supports are updated accordingly.
+///
+/// Backers that are missing from `backer_stakes` are assumed to have a stake equal to the sum of
+/// their edges in `support_map`.
+pub fn apply_slash<A: IdentifierT>(
+	support_map: &mut SupportMap<A>,
+	slashed: &A,
+	slash_fraction: Perbill,
+	backer_stakes: &mut BTreeMap<A, ExtendedBalance>,
+) {
+	let backers = match support_map.get(slashed) {
+		Some(support) => support.voters.clone(),
+		None => return,
+	};
+
+	// backer -> (old stake, new stake).
+	let mut rescaled = BTreeMap::<A, (ExtendedBalance, ExtendedBalance)>::new();
+	for (who, exposure) in backers {
+		let old = backer_stakes.get(&who).copied().unwrap_or_else(|| {
+			support_map
+				.values()
+				.flat_map(|support| support.voters.iter())
+				.filter(|(voter, _)| *voter == who)
+				.fold(0, |acc: ExtendedBalance, (_, w)| acc.saturating_add(*w))
+		});
+		let new = old.saturating_sub(slash_fraction * exposure);
+		backer_stakes.insert(who.clone(), new);
+		rescaled.insert(who, (old, new));
+	}
+
+	for support in support_map.values_mut() {
+		for (who, weight) in support.voters.iter_mut() {
+			if let Some((old, new)) = rescaled.get(who) {
+				// `new <= old`, hence this can only fail if `old` is zero, and so is the weight.
+				let scaled = multiply_by_rational(*weight, *new, *old).unwrap_or(0);
+				support.total = support.total.saturating_sub(weight.saturating_sub(scaled));
+				*weight = scaled;
+			}
+		}
+	}
+}
+
+/// Export a set of staked assignments as a JSON edge list, to be consumed by external tools.
+///
+/// The output has the shape `[{"voter": .., "edges": [{"target": .., "weight": ..}]}]`. Accounts
+/// are formatted with their `Debug` implementation; see [`assignments_to_json_with`] for a custom
+/// formatter.
+#[cfg(feature = "std")]
+pub fn assignments_to_json<A: IdentifierT>(assignments: &[StakedAssignment<A>]) -> String {
+	assignments_to_json_with(assignments, |who| format!("{:?}", who))
+}
+
+/// Same as [`assignments_to_json`], with accounts formatted by `format_account`.
+///
+/// The formatted accounts are always emitted as JSON strings.
+#[cfg(feature = "std")]
+pub fn assignments_to_json_with<A: IdentifierT, F>(
+	assignments: &[StakedAssignment<A>],
+	format_account: F,
+) -> String
+where
+	F: Fn(&A) -> String,
+{
+	let escape = |raw: String| {
+		let mut escaped = String::with_capacity(raw.len() + 2);
+		escaped.push('"');
+		for c in raw.chars() {
+			match c {
+				'"' => escaped.push_str("\\\""),
+				'\\' => escaped.push_str("\\\\"),
+				c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+				c => escaped.push(c),
+			}
+		}
+		escaped.push('"');
+		escaped
+	};
+
+	let voters = assignments
+		.iter()
+		.map(|StakedAssignment { who, distribution }| {
+			let edges = distribution
+				.iter()
+				.map(|(target, weight)| format!(
+					"{{\"target\":{},\"weight\":{}}}",
+					escape(format_account(target)),
+					weight,
+				))
+				.collect::<Vec<_>>()
+				.join(",");
+			format!("{{\"voter\":{},\"edges\":[{}]}}", escape(format_account(who)), edges)
+		})
+		.collect::<Vec<_>>()
+		.join(",");
+	format!("[{}]", voters)
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -133,4 +562,116 @@ mod tests {
 			]
 		);
 	}
+
+	#[test]
+	#[cfg(feature = "std")]
+	fn assignments_to_json_round_trips() {
+		#[derive(serde::Deserialize)]
+		struct JsonEdge {
+			target: String,
+			weight: ExtendedBalance,
+		}
+
+		#[derive(serde::Deserialize)]
+		struct JsonAssignment {
+			voter: String,
+			edges: Vec<JsonEdge>,
+		}
+
+		let from_json = |json: String| serde_json::from_str::<Vec<JsonAssignment>>(&json)
+			.unwrap()
+			.into_iter()
+			.map(|JsonAssignment { voter, edges }| (
+				voter,
+				edges.into_iter().map(|e| (e.target, e.weight)).collect::<Vec<_>>(),
+			))
+			.collect::<Vec<_>>();
+
+		let assignments = vec![
+			StakedAssignment { who: 1u32, distribution: vec![(10u32, 50), (20, 50)] },
+			StakedAssignment { who: 2u32, distribution: vec![(10, ExtendedBalance::MAX)] },
+			StakedAssignment { who: 3u32, distribution: vec![] },
+		];
+
+		assert_eq!(
+			from_json(assignments_to_json(&assignments)),
+			assignments
+				.iter()
+				.map(|a| (
+					a.who.to_string(),
+					a.distribution.iter().map(|(t, w)| (t.to_string(), *w)).collect::<Vec<_>>(),
+				))
+				.collect::<Vec<_>>(),
+		);
+
+		// custom formatters are escaped.
+		assert_eq!(
+			from_json(assignments_to_json_with(&assignments[..1], |who| format!("\"acc\\{}\"", who))),
+			vec![(
+				"\"acc\\1\"".to_string(),
+				vec![("\"acc\\10\"".to_string(), 50), ("\"acc\\20\"".to_string(), 50)],
+			)],
+		);
+	}
+
+	#[test]
+	fn zero_edge_policy_works() {
+		let dust = |who: u32| Assignment {
+			who,
+			distribution: vec![
+				(10u32, Perbill::from_percent(20)),
+				(20, Perbill::from_percent(20)),
+				(30, Perbill::from_percent(25)),
+				(40, Perbill::from_percent(20)),
+				(50, Perbill::from_percent(15)),
+			],
+		};
+		let assignments = vec![dust(1), dust(2)];
+		// 1 is dust, 2 is not.
+		let stake_of = |who: &u32| -> VoteWeight { if *who == 1 { 2 } else { 100 } };
+
+		let convert = |policy| {
+			assignment_ratio_to_staked_with_policy(assignments.clone(), stake_of, policy)
+		};
+
+		// every edge of 1 becomes zero, so it vanishes.
+		assert_eq!(
+			convert(ZeroEdgePolicy::Drop),
+			vec![
+				StakedAssignment { who: 1, distribution: vec![] },
+				StakedAssignment {
+					who: 2,
+					distribution: vec![(10, 20), (20, 20), (30, 25), (40, 20), (50, 15)],
+				},
+			],
+		);
+
+		// all the stake of 1 goes to its top target.
+		assert_eq!(
+			convert(ZeroEdgePolicy::Concentrate),
+			vec![
+				StakedAssignment { who: 1, distribution: vec![(30, 2)] },
+				StakedAssignment {
+					who: 2,
+					distribution: vec![(10, 20), (20, 20), (30, 25), (40, 20), (50, 15)],
+				},
+			],
+		);
+
+		// only some of the edges of a voter are zero: they are dropped, and the rest are kept.
+		let mixed = vec![Assignment {
+			who: 3u32,
+			distribution: vec![
+				(10u32, Perbill::from_percent(60)),
+				(20, Perbill::from_percent(39)),
+				(30, Perbill::from_percent(1)),
+			],
+		}];
+		for policy in [ZeroEdgePolicy::Drop, ZeroEdgePolicy::Concentrate].iter().cloned() {
+			assert_eq!(
+				assignment_ratio_to_staked_with_policy(mixed.clone(), |_| 10, policy),
+				vec![StakedAssignment { who: 3, distribution: vec![(10, 6), (20, 4)] }],
+			);
+		}
+	}
 }
@@ -0,0 +1,127 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An election that collects its votes one at a time.
+//!
+//! This is useful for live elections, where votes stream in and a provisional outcome needs to be
+//! displayed at any point. See [`IncrementalElection`].
+
+use crate::{
+	IdentifierT, ElectionResult, ExtendedBalance, VoteWeight, WithApprovalOf, seq_phragmen, internal,
+};
+use sp_arithmetic::{PerThing, InnerOf};
+use sp_std::{prelude::*, collections::{btree_map::BTreeMap, btree_set::BTreeSet}};
+
+/// An election to which votes can be added one by one.
+///
+/// Adding a vote only updates the approval stake of its targets, which is
+/// `O(t ⋅ log(C) + log(V))` where `t` is the number of targets of the vote, and of the vote that
+/// it replaces if any, `C` is the number of candidates and `V` the number of voters. The
+/// provisional winners are the candidates with the highest approval stake, which is cheap to
+/// compute, yet not as optimal as seq-phragmen. The full seq-phragmen result of all the votes
+/// collected so far can be computed via [`IncrementalElection::result`].
+pub struct IncrementalElection<AccountId> {
+	/// The number of winners to elect.
+	to_elect: usize,
+	/// The candidates, in their original order.
+	candidates: Vec<AccountId>,
+	/// The approval stake of each candidate.
+	approvals: BTreeMap<AccountId, ExtendedBalance>,
+	/// All the votes collected so far, keyed by voter.
+	voters: BTreeMap<AccountId, (VoteWeight, Vec<AccountId>)>,
+}
+
+impl<AccountId: IdentifierT> IncrementalElection<AccountId> {
+	/// Create a new election to elect `to_elect` winners among `candidates`, with no votes.
+	pub fn new(to_elect: usize, candidates: Vec<AccountId>) -> Self {
+		let approvals = candidates.iter().map(|c| (c.clone(), 0)).collect();
+		Self { to_elect, candidates, approvals, voters: BTreeMap::new() }
+	}
+
+	/// Add the vote of `voter` for `targets`, with the given `stake`.
+	///
+	/// Targets that are not candidates, and duplicate targets, are ignored. If `voter` has already
+	/// voted, its previous vote is replaced.
+	pub fn add_vote(&mut self, voter: AccountId, targets: Vec<AccountId>, stake: VoteWeight) {
+		if let Some((old_stake, old_targets)) = self.voters.remove(&voter) {
+			self.update_approvals(&old_targets, old_stake, false);
+		}
+
+		let mut seen = BTreeSet::new();
+		let valid_targets = targets
+			.into_iter()
+			.filter(|target| self.approvals.contains_key(target) && seen.insert(target.clone()))
+			.collect::<Vec<_>>();
+
+		self.update_approvals(&valid_targets, stake, true);
+		self.voters.insert(voter, (stake, valid_targets));
+	}
+
+	/// The provisional winners, given the votes collected so far.
+	///
+	/// These are the `to_elect` candidates with the highest approval stake, zipped with it. Ties
+	/// are broken in favour of the smaller id.
+	///
+	/// This is only an approximation of seq-phragmen, which also takes into account how much of
+	/// the stake of each voter is already used up by the winners that it has elected. Hence, these
+	/// are not guaranteed to be the winners of [`IncrementalElection::result`], even once all votes
+	/// are in.
+	pub fn current_winners(&self) -> Vec<WithApprovalOf<AccountId>> {
+		let mut ranked = self.candidates
+			.iter()
+			.map(|c| (c.clone(), self.approvals.get(c).copied().unwrap_or_default()))
+			.collect::<Vec<_>>();
+		internal::stable_sort_by_key_then_id(&mut ranked, |(_, a)| Reverse(*a), |(c, _)| c);
+		ranked.truncate(self.to_elect);
+		ranked
+	}
+
+	/// The number of votes collected so far.
+	pub fn vote_count(&self) -> usize {
+		self.voters.len()
+	}
+
+	/// Execute seq-phragmen over all the votes collected so far, with the voters sorted by id.
+	///
+	/// See [`seq_phragmen`] for more information.
+	pub fn result<P: PerThing>(
+		&self,
+		balance: Option<(usize, ExtendedBalance)>,
+	) -> Result<ElectionResult<AccountId, P>, &'static str> where ExtendedBalance: From<InnerOf<P>> {
+		seq_phragmen::<AccountId, P>(
+			self.to_elect,
+			self.candidates.clone(),
+			self.voters
+				.iter()
+				.map(|(who, (stake, targets))| (who.clone(), *stake, targets.clone()))
+				.collect(),
+			balance,
+		)
+	}
+
+	fn update_approvals(&mut self, targets: &[AccountId], stake: VoteWeight, add: bool) {
+		for target in targets {
+			if let Some(approval) = self.approvals.get_mut(target) {
+				*approval = if add {
+					approval.saturating_add(stake.into())
+				} else {
+					approval.saturating_sub(stake.into())
+				};
+			}
+		}
+	}
+}
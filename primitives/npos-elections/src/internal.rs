@@ -0,0 +1,192 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Internal utilities shared across the crate.
+
+use sp_std::prelude::*;
+
+/// Sort `items` by the account id returned by `id_of`.
+///
+/// The sort is stable, hence items with equal ids retain their relative order. This should be
+/// used anywhere accounts are ordered, so that the outputs of the crate are reproducible
+/// regardless of the order of the inputs, in `std` and `no_std` alike.
+pub(crate) fn stable_sort_by_id<T, A: Ord, F>(items: &mut [T], id_of: F)
+where
+	F: Fn(&T) -> &A,
+{
+	items.sort_by(|x, y| id_of(x).cmp(id_of(y)));
+}
+
+/// Sort `items` by the key returned by `key_of`, and items with equal keys by the account id
+/// returned by `id_of`.
+///
+/// This is [`stable_sort_by_id`] followed by a stable sort by key, hence items with equal keys and
+/// ids retain their relative order.
+pub(crate) fn stable_sort_by_key_then_id<T, K: Ord, A: Ord, G, F>(
+	items: &mut [T],
+	key_of: G,
+	id_of: F,
+) where
+	G: Fn(&T) -> K,
+	F: Fn(&T) -> &A,
+{
+	stable_sort_by_id(items, id_of);
+	items.sort_by_key(key_of);
+}
+
+/// A minimal deterministic pseudo-random number generator (SplitMix64).
+///
+/// This is by no means cryptographically secure, and is only meant to derive a reproducible order
+/// from a seed, without pulling in a random number generator crate in `no_std`.
+pub(crate) struct SplitMix64(u64);
+
+impl SplitMix64 {
+	/// Create a new generator from the given seed.
+	pub(crate) fn new(seed: u64) -> Self {
+		Self(seed)
+	}
+
+	/// Return the next pseudo-random number.
+	pub(crate) fn next_u64(&mut self) -> u64 {
+		self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+		let mut z = self.0;
+		z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+		z ^ (z >> 31)
+	}
+}
+
+/// A source of random draws, used wherever the crate needs seeded randomness.
+pub(crate) trait RandomSource {
+	/// Return the next random draw.
+	fn next_u32(&mut self) -> u32;
+
+	/// Shuffle `items` in place (Fisher-Yates), using one draw per item but the first.
+	fn shuffle<T>(&mut self, items: &mut [T]) {
+		for i in (1..items.len()).rev() {
+			let j = (self.next_u32() as usize) % (i + 1);
+			items.swap(i, j);
+		}
+	}
+}
+
+impl RandomSource for SplitMix64 {
+	fn next_u32(&mut self) -> u32 {
+		(self.next_u64() >> 32) as u32
+	}
+}
+
+/// A [`RandomSource`] that records all the draws of another source.
+pub(crate) struct Recorder<'a, R> {
+	/// The source of the draws.
+	pub(crate) inner: R,
+	/// All the draws so far.
+	pub(crate) record: &'a mut Vec<u32>,
+}
+
+impl<'a, R: RandomSource> RandomSource for Recorder<'a, R> {
+	fn next_u32(&mut self) -> u32 {
+		let draw = self.inner.next_u32();
+		self.record.push(draw);
+		draw
+	}
+}
+
+/// A [`RandomSource`] that replays a recorded stream of draws.
+///
+/// Once the stream is exhausted, zero is returned and the replay is marked as exhausted.
+pub(crate) struct Replay<'a> {
+	/// The recorded draws.
+	stream: &'a [u32],
+	/// The index of the next draw.
+	position: usize,
+	/// True if more draws than recorded have been requested.
+	pub(crate) exhausted: bool,
+}
+
+impl<'a> Replay<'a> {
+	/// Create a new replay of the given stream.
+	pub(crate) fn new(stream: &'a [u32]) -> Self {
+		Self { stream, position: 0, exhausted: false }
+	}
+}
+
+impl<'a> RandomSource for Replay<'a> {
+	fn next_u32(&mut self) -> u32 {
+		match self.stream.get(self.position) {
+			Some(draw) => {
+				self.position += 1;
+				*draw
+			},
+			None => {
+				self.exhausted = true;
+				0
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn stable_sort_by_id_is_stable() {
+		let mut items = vec![(3, 'a'), (1, 'b'), (3, 'c'), (2, 'd'), (1, 'e'), (3, 'f')];
+		stable_sort_by_id(&mut items, |(id, _)| id);
+		assert_eq!(items, vec![(1, 'b'), (1, 'e'), (2, 'd'), (3, 'a'), (3, 'c'), (3, 'f')]);
+	}
+
+	#[test]
+	fn stable_sort_by_key_then_id_works() {
+		let mut items = vec![(3, 10, 'a'), (1, 20, 'b'), (2, 10, 'c'), (1, 10, 'd'), (1, 20, 'e')];
+		stable_sort_by_key_then_id(&mut items, |(_, key, _)| Reverse(*key), |(id, _, _)| id);
+		assert_eq!(
+			items,
+			vec![(1, 20, 'b'), (1, 20, 'e'), (1, 10, 'd'), (2, 10, 'c'), (3, 10, 'a')],
+		);
+	}
+
+	#[test]
+	fn record_and_replay_works() {
+		let mut record = Vec::new();
+		let mut recorder = Recorder { inner: SplitMix64::new(7), record: &mut record };
+		let draws = (0..5).map(|_| recorder.next_u32()).collect::<Vec<_>>();
+		assert_eq!(record, draws);
+
+		let mut replay = Replay::new(&record);
+		assert_eq!((0..5).map(|_| replay.next_u32()).collect::<Vec<_>>(), draws);
+		assert!(!replay.exhausted);
+		assert_eq!(replay.next_u32(), 0);
+		assert!(replay.exhausted);
+	}
+
+	#[test]
+	fn shuffle_is_deterministic() {
+		let shuffled = |seed| {
+			let mut items = (0..10).collect::<Vec<_>>();
+			SplitMix64::new(seed).shuffle(&mut items);
+			items
+		};
+		assert_eq!(shuffled(42), shuffled(42));
+		assert_ne!(shuffled(42), shuffled(43));
+
+		let mut items = shuffled(42);
+		items.sort();
+		assert_eq!(items, (0..10).collect::<Vec<_>>());
+	}
+}
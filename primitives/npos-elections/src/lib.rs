@@ -78,8 +78,8 @@ use sp_std::{
 	prelude::*, collections::btree_map::BTreeMap, fmt::Debug, cmp::Ordering, rc::Rc, cell::RefCell,
 };
 use sp_arithmetic::{
-	PerThing, Rational128, ThresholdOrd, InnerOf, Normalizable,
-	traits::{Zero, Bounded},
+	PerThing, Perbill, Rational128, ThresholdOrd, InnerOf, Normalizable, FixedU128,
+	FixedPointNumber, traits::{Zero, Bounded, Saturating},
 };
 
 #[cfg(feature = "std")]
@@ -98,12 +98,25 @@ mod phragmms;
 mod node;
 mod reduce;
 mod helpers;
+mod analysis;
+mod incremental;
+mod internal;
+mod seats;
 
-pub use reduce::reduce;
+pub use reduce::{
+	reduce, reduce_prioritized, reduce_with_forest, min_edges_bound, verify_reduce_step,
+	ReduceStep, VerifyError, Forest,
+};
+pub use node::{NodeId, NodeRole};
+#[cfg(feature = "parallel")]
+pub use reduce::reduce_parallel;
 pub use helpers::*;
 pub use phragmen::*;
 pub use phragmms::*;
 pub use balancing::*;
+pub use analysis::*;
+pub use incremental::*;
+pub use seats::*;
 
 // re-export the compact macro, with the dependencies of the macro.
 #[doc(hidden)]
@@ -151,6 +164,10 @@ pub enum Error {
 	CompactInvalidIndex,
 	/// An error occurred in some arithmetic operation.
 	ArithmeticError(&'static str),
+	/// An edge pointing to a non-winner target was found while building a support map.
+	InvalidSupportEdge,
+	/// The spanning forest of a reduction was found to contain a cycle.
+	CyclicForest,
 }
 
 /// A type which is used in the API of this crate as a numeric weight of a vote, most often the
@@ -163,6 +180,33 @@ pub type ExtendedBalance = u128;
 /// The score of an assignment. This can be computed from the support map via [`evaluate_support`].
 pub type ElectionScore = [ExtendedBalance; 3];
 
+/// A human readable version of an [`ElectionScore`], with named fields.
+///
+/// This is mostly useful for logging, or to be returned by RPCs.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct ScoreReport {
+	/// The minimal support of any winner. This value must be **maximized**.
+	pub minimal_stake: ExtendedBalance,
+	/// The sum of the supports of all winners. This value must be **maximized**.
+	pub sum_stake: ExtendedBalance,
+	/// The sum of the supports of all winners squared. This value must be **minimized**.
+	pub sum_stake_squared: ExtendedBalance,
+}
+
+impl From<ElectionScore> for ScoreReport {
+	fn from(score: ElectionScore) -> Self {
+		let [minimal_stake, sum_stake, sum_stake_squared] = score;
+		Self { minimal_stake, sum_stake, sum_stake_squared }
+	}
+}
+
+impl From<ScoreReport> for ElectionScore {
+	fn from(report: ScoreReport) -> Self {
+		[report.minimal_stake, report.sum_stake, report.sum_stake_squared]
+	}
+}
+
 /// A winner, with their respective approval stake.
 pub type WithApprovalOf<A> = (A, ExtendedBalance);
 
@@ -321,6 +365,375 @@ pub struct ElectionResult<AccountId, P: PerThing> {
 	pub assignments: Vec<Assignment<AccountId, P>>,
 }
 
+/// The maximum number of backers listed in a [`WinnerExplanation`].
+pub const EXPLANATION_TOP_BACKERS: usize = 3;
+
+/// A short, machine readable explanation of why a winner has been elected.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct WinnerExplanation<AccountId> {
+	/// The round at which this winner was elected, i.e. its index in [`ElectionResult::winners`].
+	pub round: usize,
+	/// The backing stake of the winner, as reported by the election algorithm.
+	pub backing: ExtendedBalance,
+	/// The total support of the winner in the support map. Zero if it has no support.
+	pub support: ExtendedBalance,
+	/// The voters with the highest contribution to the support of this winner, at most
+	/// [`EXPLANATION_TOP_BACKERS`] of them. Sorted by contribution, descending, and then by
+	/// identifier.
+	pub top_backers: Vec<(AccountId, ExtendedBalance)>,
+}
+
+impl<AccountId: IdentifierT, P: PerThing> ElectionResult<AccountId, P> {
+	/// Iterate over all the edges of this result as `(voter, target, weight)`, where the ratios
+	/// of the assignments are converted into stake weights using `stake_of`.
+	///
+	/// The conversion is lazy and is the same as [`assignment_ratio_to_staked`], thus the weights
+	/// are NOT normalized.
+	pub fn edges<'a, FS>(
+		&'a self,
+		stake_of: FS,
+	) -> impl Iterator<Item = (AccountId, AccountId, ExtendedBalance)> + 'a
+	where
+		FS: Fn(&AccountId) -> VoteWeight + 'a,
+		P: sp_std::ops::Mul<ExtendedBalance, Output = ExtendedBalance>,
+		ExtendedBalance: From<InnerOf<P>>,
+	{
+		self.assignments.iter().flat_map(move |assignment| {
+			let stake = stake_of(&assignment.who).into();
+			let StakedAssignment { who, distribution } = assignment.clone().into_staked(stake);
+			distribution.into_iter().map(move |(target, weight)| (who.clone(), target, weight))
+		})
+	}
+
+	/// Explain why each of the winners of this election result has been elected, using the given
+	/// `support_map`, which must be built from this election result.
+	///
+	/// The explanation is deterministic, given the same inputs.
+	pub fn explanations(
+		&self,
+		support_map: &SupportMap<AccountId>,
+	) -> BTreeMap<AccountId, WinnerExplanation<AccountId>> {
+		self.winners
+			.iter()
+			.enumerate()
+			.map(|(round, (who, backing))| {
+				let (support, mut backers) = support_map
+					.get(who)
+					.map(|s| (s.total, s.voters.clone()))
+					.unwrap_or_default();
+				internal::stable_sort_by_key_then_id(&mut backers, |(_, w)| Reverse(*w), |(x, _)| x);
+				backers.truncate(EXPLANATION_TOP_BACKERS);
+
+				let explanation = WinnerExplanation {
+					round,
+					backing: *backing,
+					support,
+					top_backers: backers,
+				};
+				(who.clone(), explanation)
+			})
+			.collect()
+	}
+
+	/// Convert this result into the format in which the staking pallet stores an election, namely
+	/// the list of validators and the exposure of each of them, using `stake_of`.
+	///
+	/// This follows the same steps as the pallet: the assignments are converted into normalized
+	/// staked assignments, from which a support map is built, and the self vote of each validator
+	/// is then separated from the votes of its nominators, see [`split_exposure`]. The validators
+	/// are in the order of [`ElectionResult::winners`], and the exposures are sorted by validator.
+	/// All values are in [`ExtendedBalance`], and are yet to be converted into the currency of the
+	/// chain.
+	pub fn to_staking_storage_format<FS>(
+		self,
+		stake_of: FS,
+	) -> Result<StakingStorageFormat<AccountId>, Error>
+	where
+		for<'r> FS: Fn(&'r AccountId) -> VoteWeight,
+		P: sp_std::ops::Mul<ExtendedBalance, Output = ExtendedBalance>,
+		ExtendedBalance: From<InnerOf<P>>,
+	{
+		let validators = to_without_backing(self.winners);
+		let staked = assignment_ratio_to_staked_normalized(self.assignments, stake_of)?;
+		let supports = build_support_map(&validators, &staked)
+			.map_err(|_| Error::InvalidSupportEdge)?;
+
+		let exposures = supports
+			.into_iter()
+			.map(|(validator, support)| {
+				let (own, others) = split_exposure(&support, &validator);
+				let others = others
+					.into_iter()
+					.map(|(who, value)| IndividualExposure { who, value })
+					.collect();
+				(validator, Exposure { total: support.total, own, others })
+			})
+			.collect();
+
+		Ok((validators, exposures))
+	}
+
+	/// Compress this result by factoring out the lists of targets that are shared among voters.
+	///
+	/// Each distinct list of targets is stored only once, and is referenced by index from all of
+	/// the assignments that use it, which only keep their ratios. This shrinks the encoded size of
+	/// the result if many voters back the exact same targets, in the same order. See
+	/// [`CompressedResult::decompress`] for the inverse.
+	pub fn compress_shared(self) -> CompressedResult<AccountId, P> {
+		let mut group_index = BTreeMap::<Vec<AccountId>, u32>::new();
+		let mut groups = Vec::new();
+		let assignments = self.assignments
+			.into_iter()
+			.map(|Assignment { who, distribution }| {
+				let (targets, ratios): (Vec<_>, Vec<_>) = distribution.into_iter().unzip();
+				let group = match group_index.get(&targets) {
+					Some(group) => *group,
+					None => {
+						let group = groups.len() as u32;
+						group_index.insert(targets.clone(), group);
+						groups.push(targets);
+						group
+					},
+				};
+				CompressedAssignment { who, group, ratios }
+			})
+			.collect();
+
+		CompressedResult { winners: self.winners, groups, assignments }
+	}
+}
+
+/// The exposure of a nominator to a validator, with the same layout as that of the staking
+/// pallet.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "std", derive(PartialEq, Eq, Encode, Decode))]
+pub struct IndividualExposure<AccountId> {
+	/// The nominator.
+	pub who: AccountId,
+	/// The exposed stake of the nominator.
+	#[cfg_attr(feature = "std", codec(compact))]
+	pub value: ExtendedBalance,
+}
+
+/// The stake backing a single validator, with the same layout as that of the staking pallet.
+///
+/// See [`ElectionResult::to_staking_storage_format`].
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "std", derive(PartialEq, Eq, Encode, Decode))]
+pub struct Exposure<AccountId> {
+	/// The total stake backing the validator.
+	#[cfg_attr(feature = "std", codec(compact))]
+	pub total: ExtendedBalance,
+	/// The stake of the validator itself.
+	#[cfg_attr(feature = "std", codec(compact))]
+	pub own: ExtendedBalance,
+	/// The stakes of the nominators of the validator.
+	pub others: Vec<IndividualExposure<AccountId>>,
+}
+
+/// The list of validators and their exposures, as returned by
+/// [`ElectionResult::to_staking_storage_format`].
+pub type StakingStorageFormat<AccountId> = (Vec<AccountId>, Vec<(AccountId, Exposure<AccountId>)>);
+
+/// An assignment of a [`CompressedResult`].
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "std", derive(PartialEq, Eq, Encode, Decode))]
+pub struct CompressedAssignment<AccountId, P> {
+	/// Voter's identifier.
+	pub who: AccountId,
+	/// The index of the list of targets of this voter in [`CompressedResult::groups`].
+	#[cfg_attr(feature = "std", codec(compact))]
+	pub group: u32,
+	/// The ratio of the voter's stake that is assigned to each of the targets of its group.
+	pub ratios: Vec<P>,
+}
+
+/// An [`ElectionResult`] in which the lists of targets shared among voters are stored only once.
+///
+/// See [`ElectionResult::compress_shared`].
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "std", derive(PartialEq, Eq, Encode, Decode))]
+pub struct CompressedResult<AccountId, P> {
+	/// Same as [`ElectionResult::winners`].
+	pub winners: Vec<WithApprovalOf<AccountId>>,
+	/// All the distinct lists of targets, in the order of their first use.
+	pub groups: Vec<Vec<AccountId>>,
+	/// The compressed assignments.
+	pub assignments: Vec<CompressedAssignment<AccountId, P>>,
+}
+
+impl<AccountId: IdentifierT, P: PerThing> CompressedResult<AccountId, P> {
+	/// Restore the [`ElectionResult`] from which this was compressed.
+	///
+	/// Fails with [`Error::CompactInvalidIndex`] if an assignment refers to a group that does not
+	/// exist, or if its number of ratios does not match the size of its group.
+	pub fn decompress(self) -> Result<ElectionResult<AccountId, P>, Error> {
+		let groups = self.groups;
+		let assignments = self.assignments
+			.into_iter()
+			.map(|CompressedAssignment { who, group, ratios }| {
+				let targets = groups.get(group as usize).ok_or(Error::CompactInvalidIndex)?;
+				if targets.len() != ratios.len() {
+					return Err(Error::CompactInvalidIndex);
+				}
+				let distribution = targets.iter().cloned().zip(ratios).collect();
+				Ok(Assignment { who, distribution })
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+
+		Ok(ElectionResult { winners: self.winners, assignments })
+	}
+}
+
+/// An aggregate of the most commonly queried properties of an election, to be returned by RPCs.
+///
+/// See [`summarize`].
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct ElectionSummary {
+	/// The number of winners.
+	pub winner_count: u32,
+	/// The sum of the supports of all winners.
+	pub total_stake: ExtendedBalance,
+	/// The minimum support of any winner.
+	pub min_support: ExtendedBalance,
+	/// The maximum support of any winner.
+	pub max_support: ExtendedBalance,
+	/// The mean support of the winners, rounded down.
+	pub mean_support: ExtendedBalance,
+	/// The nakamoto coefficient of the winners. See [`nakamoto_coefficient`].
+	pub nakamoto_coefficient: u32,
+	/// The backing stake of the weakest winner, before any balancing. See [`entry_threshold`].
+	pub entry_threshold: ExtendedBalance,
+}
+
+/// Summarize an election `result` and its `support_map`, which must be built from `result`.
+/// `candidates` and `voters` are the inputs of the election, which are needed for the entry
+/// threshold.
+///
+/// Only the supports of the winners of `result` are taken into account, and a winner that is not
+/// in the map has zero support, same as [`min_support_of_winners`]. The entry threshold is the one
+/// of [`entry_threshold`], with as many rounds as there are winners. All the values are zero if
+/// there are no winners.
+///
+/// This can only fail if the normalization fails.
+pub fn summarize<AccountId: IdentifierT, P: PerThing>(
+	result: &ElectionResult<AccountId, P>,
+	support_map: &SupportMap<AccountId>,
+	candidates: Vec<AccountId>,
+	voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+) -> Result<ElectionSummary, &'static str> {
+	if result.winners.is_empty() {
+		return Ok(Default::default())
+	}
+
+	let winners = to_without_backing(result.winners.clone());
+	// only the totals are needed, the voters of each support are left out.
+	let winner_supports = winners
+		.iter()
+		.map(|w| {
+			let total = support_map.get(w).map(|s| s.total).unwrap_or_else(Zero::zero);
+			(w.clone(), Support { total, voters: Vec::new() })
+		})
+		.collect::<SupportMap<AccountId>>();
+
+	let [_, total_stake, _] = evaluate_support(&winner_supports);
+	Ok(ElectionSummary {
+		winner_count: winners.len() as u32,
+		total_stake,
+		min_support: min_support_of_winners(support_map, &winners),
+		max_support: winner_supports.values().map(|s| s.total).max().unwrap_or_default(),
+		mean_support: total_stake / winners.len() as ExtendedBalance,
+		nakamoto_coefficient: nakamoto_coefficient(&winner_supports),
+		entry_threshold: entry_threshold(candidates, voters, winners.len())?,
+	})
+}
+
+/// The outcome of [`cross_check`]. Lists all the divergences between two election results.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct CrossCheckReport<AccountId> {
+	/// Winners of the first result that are not winners of the second one. Sorted.
+	pub winners_only_in_a: Vec<AccountId>,
+	/// Winners of the second result that are not winners of the first one. Sorted.
+	pub winners_only_in_b: Vec<AccountId>,
+	/// Voters whose assignments are not the same in both results. Sorted.
+	pub diverging_voters: Vec<AccountId>,
+}
+
+impl<AccountId> CrossCheckReport<AccountId> {
+	/// True if both results have the same set of winners.
+	pub fn winners_agree(&self) -> bool {
+		self.winners_only_in_a.is_empty() && self.winners_only_in_b.is_empty()
+	}
+
+	/// True if both results are semantically the same, i.e. there is no divergence at all.
+	pub fn agrees(&self) -> bool {
+		self.winners_agree() && self.diverging_voters.is_empty()
+	}
+}
+
+/// Compare two election results that are meant to be computed by two independent
+/// implementations over the same inputs.
+///
+/// Winners are compared as sets, ignoring their order and backing stake. Assignments are
+/// compared semantically: the order of assignments and distributions does not matter and zero
+/// ratios are ignored, but the ratios must otherwise be exactly the same.
+pub fn cross_check<AccountId: IdentifierT, P: PerThing>(
+	result_a: &ElectionResult<AccountId, P>,
+	result_b: &ElectionResult<AccountId, P>,
+) -> CrossCheckReport<AccountId> {
+	let winners_of = |result: &ElectionResult<AccountId, P>| result
+		.winners
+		.iter()
+		.map(|(who, _)| (who.clone(), ()))
+		.collect::<BTreeMap<_, _>>();
+	let winners_a = winners_of(result_a);
+	let winners_b = winners_of(result_b);
+	let only_in = |this: &BTreeMap<AccountId, ()>, other: &BTreeMap<AccountId, ()>| this
+		.keys()
+		.filter(|who| !other.contains_key(who))
+		.cloned()
+		.collect::<Vec<_>>();
+
+	let distributions_of = |result: &ElectionResult<AccountId, P>| {
+		let mut distributions = BTreeMap::<AccountId, BTreeMap<AccountId, P>>::new();
+		for assignment in result.assignments.iter() {
+			let distribution = assignment
+				.distribution
+				.iter()
+				.filter(|(_, r)| !r.is_zero())
+				.cloned()
+				.collect::<BTreeMap<_, _>>();
+			if !distribution.is_empty() {
+				distributions.entry(assignment.who.clone()).or_default().extend(distribution);
+			}
+		}
+		distributions
+	};
+	let distributions_a = distributions_of(result_a);
+	let distributions_b = distributions_of(result_b);
+	let mut diverging_voters = distributions_a
+		.iter()
+		.filter(|(who, distribution)| distributions_b.get(who) != Some(distribution))
+		.map(|(who, _)| who.clone())
+		.chain(
+			distributions_b
+				.keys()
+				.filter(|who| !distributions_a.contains_key(who))
+				.cloned(),
+		)
+		.collect::<Vec<_>>();
+	internal::stable_sort_by_id(&mut diverging_voters, |who| who);
+
+	CrossCheckReport {
+		winners_only_in_a: only_in(&winners_a, &winners_b),
+		winners_only_in_b: only_in(&winners_b, &winners_a),
+		diverging_voters,
+	}
+}
+
 /// A voter's stake assignment among a set of targets, represented as ratios.
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "std", derive(PartialEq, Eq, Encode, Decode))]
@@ -392,6 +805,49 @@ where
 					.for_each(|((_, old), corrected)| { *old = corrected; })
 			)
 	}
+
+	/// Check that this assignment is valid, given the set of `winners` of the election.
+	///
+	/// This is meant to be used on untrusted input, e.g. a submitted solution. An assignment is
+	/// valid if its distribution is not empty, if all of its targets are winners and distinct, and
+	/// if its ratios sum up to one. Since each ratio can be rounded by at most one part, the sum is
+	/// allowed to deviate from one by at most one part per target.
+	pub fn validate(&self, winners: &[AccountId]) -> Result<(), AssignmentError> {
+		if self.distribution.is_empty() {
+			return Err(AssignmentError::EmptyDistribution);
+		}
+		for (index, (target, _)) in self.distribution.iter().enumerate() {
+			if !winners.contains(target) {
+				return Err(AssignmentError::NonWinnerTarget);
+			}
+			if self.distribution[..index].iter().any(|(other, _)| other == target) {
+				return Err(AssignmentError::DuplicateTarget);
+			}
+		}
+
+		let sum = self.distribution
+			.iter()
+			.fold(0, |acc: ExtendedBalance, (_, p)| acc.saturating_add(p.deconstruct().into()));
+		let one: ExtendedBalance = P::ACCURACY.into();
+		let tolerance = self.distribution.len() as ExtendedBalance;
+		if sum.max(one) - sum.min(one) > tolerance {
+			return Err(AssignmentError::InvalidRatioSum);
+		}
+		Ok(())
+	}
+}
+
+/// The reasons for which an [`Assignment`] might be invalid, see [`Assignment::validate`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum AssignmentError {
+	/// The distribution is empty.
+	EmptyDistribution,
+	/// One of the targets is not a winner.
+	NonWinnerTarget,
+	/// The same target appears more than once.
+	DuplicateTarget,
+	/// The ratios do not sum up to one.
+	InvalidRatioSum,
 }
 
 /// A voter's stake assignment among a set of targets, represented as absolute values in the scale
@@ -488,9 +944,155 @@ pub struct Support<AccountId> {
 	pub voters: Vec<(AccountId, ExtendedBalance)>,
 }
 
+impl<AccountId> Support<AccountId> {
+	/// Create a new support, only if `total` is exactly the sum of the weights of all `voters`.
+	///
+	/// The fields of a support can be set directly, which allows for a support whose total does
+	/// not match its voters to be built. This should be used instead wherever a support is built
+	/// by hand, e.g. in tests and tooling.
+	pub fn new_checked(
+		total: ExtendedBalance,
+		voters: Vec<(AccountId, ExtendedBalance)>,
+	) -> Result<Self, &'static str> {
+		let sum = voters
+			.iter()
+			.try_fold(ExtendedBalance::zero(), |acc, (_, w)| acc.checked_add(*w))
+			.ok_or("sum of voter weights overflows")?;
+		if sum != total {
+			return Err("total does not match the sum of voter weights");
+		}
+		Ok(Self { total, voters })
+	}
+
+	/// The share of the largest voter in the total backing of this support.
+	///
+	/// A value close to one means that the backing of this target is dangerously single-sourced.
+	/// If the total support is zero, then the risk is zero as well.
+	pub fn concentration_risk(&self) -> Perbill {
+		if self.total.is_zero() {
+			return Perbill::zero();
+		}
+		let largest = self.voters.iter().map(|(_, w)| *w).max().unwrap_or_else(Zero::zero);
+		Perbill::from_rational_approximation(largest, self.total)
+	}
+}
+
 /// A linkage from a candidate and its [`Support`].
 pub type SupportMap<A> = BTreeMap<A, Support<A>>;
 
+/// Extension methods for [`SupportMap`].
+pub trait SupportMapExt<A> {
+	/// Retain only the `n` targets with the highest total support, dropping the rest along with
+	/// all of their voters. Ties are broken in favour of the smaller id.
+	fn keep_top(&mut self, n: usize);
+
+	/// The effective number of targets, namely the inverse of the sum of the squares of the
+	/// shares of all targets in the total support (Laakso-Taagepera).
+	///
+	/// This is equal to the number of targets if all of them have the same support, and is close
+	/// to one if a single target dominates all the others. An empty map, or one with no support at
+	/// all, yields zero. Since this is not a ratio, it is returned as a fixed point number.
+	fn effective_validator_count(&self) -> FixedU128;
+
+	/// Apply a delta of `(voter, target, weight)` edges to this map in place, first all of the
+	/// `removed` ones and then all of the `added` ones.
+	///
+	/// Removing the full weight of an edge removes the voter from the support of the target.
+	/// Adding an edge for a voter who already backs the target increases its weight, otherwise the
+	/// voter is inserted into the support of the target, keeping the voters sorted. The outcome is
+	/// the same as that of [`build_support_map`] with the delta applied to the assignments.
+	///
+	/// This fails with the target of the first edge which cannot be applied, either because the
+	/// target is not in the map or because the edge to be removed does not exist with at least the
+	/// given weight. In that case, the map is left untouched.
+	fn apply_delta(
+		&mut self,
+		removed: &[(A, A, ExtendedBalance)],
+		added: &[(A, A, ExtendedBalance)],
+	) -> Result<(), A>;
+}
+
+impl<A: IdentifierT> SupportMapExt<A> for SupportMap<A> {
+	fn keep_top(&mut self, n: usize) {
+		if self.len() <= n {
+			return
+		}
+		let mut ranked = self.iter().map(|(who, s)| (who.clone(), s.total)).collect::<Vec<_>>();
+		internal::stable_sort_by_key_then_id(&mut ranked, |(_, t)| Reverse(*t), |(who, _)| who);
+		for (who, _) in ranked.into_iter().skip(n) {
+			self.remove(&who);
+		}
+	}
+
+	fn effective_validator_count(&self) -> FixedU128 {
+		let total = self.values().fold(0, |acc: ExtendedBalance, s| acc.saturating_add(s.total));
+		if total.is_zero() {
+			return FixedU128::zero();
+		}
+		let sum_of_squares = self.values().fold(FixedU128::zero(), |acc, s| {
+			let share = FixedU128::saturating_from_rational(s.total, total);
+			acc.saturating_add(share.saturating_mul(share))
+		});
+		sum_of_squares.reciprocal().unwrap_or_else(FixedU128::zero)
+	}
+
+	fn apply_delta(
+		&mut self,
+		removed: &[(A, A, ExtendedBalance)],
+		added: &[(A, A, ExtendedBalance)],
+	) -> Result<(), A> {
+		// check the whole delta first, so that nothing is applied if any edge fails. The same edge
+		// can be removed more than once, hence the removed weights are accumulated.
+		let mut to_remove = BTreeMap::<(A, A), ExtendedBalance>::new();
+		for (voter, target, weight) in removed {
+			let support = self.get(target).ok_or_else(|| target.clone())?;
+			let existing = support.voters
+				.iter()
+				.find(|(v, _)| v == voter)
+				.map(|(_, w)| *w)
+				.ok_or_else(|| target.clone())?;
+			let total = to_remove.entry((voter.clone(), target.clone())).or_insert(0);
+			*total = total.saturating_add(*weight);
+			if *total > existing {
+				return Err(target.clone());
+			}
+		}
+		if let Some((_, target, _)) = added.iter().find(|(_, t, _)| !self.contains_key(t)) {
+			return Err(target.clone());
+		}
+
+		for (voter, target, weight) in removed {
+			let support = self.get_mut(target).expect("target is checked above; qed");
+			let index = support.voters
+				.iter()
+				.position(|(v, _)| v == voter)
+				.expect("edge is checked above; qed");
+			support.voters[index].1 -= weight;
+			if support.voters[index].1.is_zero() {
+				support.voters.remove(index);
+			}
+			support.total = support.total.saturating_sub(*weight);
+		}
+
+		for (voter, target, weight) in added {
+			let support = self.get_mut(target).expect("target is checked above; qed");
+			match support.voters.iter_mut().find(|(v, _)| v == voter) {
+				Some((_, w)) => *w = w.saturating_add(*weight),
+				None => {
+					let index = support.voters
+						.iter()
+						.position(|(v, _)| v > voter)
+						.unwrap_or(support.voters.len());
+					support.voters.insert(index, (voter.clone(), *weight));
+				},
+			}
+			support.total = support.total.saturating_add(*weight);
+		}
+
+		Ok(())
+	}
+}
+
 /// Build the support map from the given election result. It maps a flat structure like
 ///
 /// ```nocompile
@@ -521,6 +1123,8 @@ pub type SupportMap<A> = BTreeMap<A, Support<A>>;
 /// winner from the given winner set. A value in this place larger than 0 indicates a potentially
 /// faulty assignment.
 ///
+/// The voters of each support are sorted by their id, regardless of the order of `assignments`.
+///
 /// `O(E)` where `E` is the total number of edges.
 pub fn build_support_map<AccountId>(
 	winners: &[AccountId],
@@ -545,6 +1149,10 @@ pub fn build_support_map<AccountId>(
 			}
 		}
 	}
+	// the order of the backers must not depend on the order of the assignments.
+	supports
+		.values_mut()
+		.for_each(|support| internal::stable_sort_by_id(&mut support.voters, |(who, _)| who));
 	Ok(supports)
 }
 
@@ -574,6 +1182,115 @@ pub fn evaluate_support<AccountId>(
 	[min_support, sum, sum_squared]
 }
 
+/// Same as [`evaluate_support`], but the total support of each target is first discounted by its
+/// commission, as given by `commission_of`.
+///
+/// A target with a commission of 10% thus only counts with 90% of its support. Comparing such
+/// scores, e.g. with [`is_score_better`], accounts for the share of the support that is not taken
+/// by the commission of the validators.
+///
+/// `O(T)` where `T` is the number of targets in the map.
+pub fn commission_adjusted_score<AccountId, FC>(
+	support: &SupportMap<AccountId>,
+	commission_of: FC,
+) -> ElectionScore where
+	FC: Fn(&AccountId) -> Perbill,
+{
+	let mut min_support = ExtendedBalance::max_value();
+	let mut sum: ExtendedBalance = Zero::zero();
+	let mut sum_squared: ExtendedBalance = Zero::zero();
+	for (who, support) in support.iter() {
+		let total = Perbill::one().saturating_sub(commission_of(who)) * support.total;
+		sum = sum.saturating_add(total);
+		sum_squared = sum_squared.saturating_add(total.saturating_mul(total));
+		if total < min_support {
+			min_support = total;
+		}
+	}
+	[min_support, sum, sum_squared]
+}
+
+/// Compute the minimum support of `winners` in `support_map`, i.e. the first element of
+/// [`evaluate_support`] with all the other targets of the map ignored.
+///
+/// A winner that is not in the map has zero support. Same as [`evaluate_support`], if there are no
+/// winners, `ExtendedBalance::max_value()` is returned.
+///
+/// `O(W ⋅ log(T))` where `W` is the number of winners and `T` the number of targets in the map.
+pub fn min_support_of_winners<AccountId: IdentifierT>(
+	support_map: &SupportMap<AccountId>,
+	winners: &[AccountId],
+) -> ExtendedBalance {
+	winners
+		.iter()
+		.map(|who| support_map.get(who).map(|s| s.total).unwrap_or_else(Zero::zero))
+		.min()
+		.unwrap_or_else(Bounded::max_value)
+}
+
+/// Same as [`evaluate_support`] over the support map that [`build_support_map`] would build from
+/// `winners` and `assignments`, without materializing it.
+///
+/// Only the total support of each winner is kept, and assignments are consumed one by one, so
+/// they never need to be held in memory all at once. The returned score is exactly the same as
+/// that of the batch evaluation. Similar to [`build_support_map`], the first target that is not a
+/// winner is returned as an error.
+///
+/// `O(E)` where `E` is the total number of edges.
+pub fn evaluate_support_streaming<AccountId, I>(
+	winners: &[AccountId],
+	assignments: I,
+) -> Result<ElectionScore, AccountId> where
+	AccountId: IdentifierT,
+	I: Iterator<Item = StakedAssignment<AccountId>>,
+{
+	let mut totals = winners
+		.iter()
+		.map(|w| (w.clone(), ExtendedBalance::zero()))
+		.collect::<BTreeMap<_, _>>();
+	let mut sum: ExtendedBalance = Zero::zero();
+	for StakedAssignment { distribution, .. } in assignments {
+		for (target, weight) in distribution {
+			match totals.get_mut(&target) {
+				Some(total) => *total = total.saturating_add(weight),
+				None => return Err(target),
+			}
+			sum = sum.saturating_add(weight);
+		}
+	}
+
+	let mut min_support = ExtendedBalance::max_value();
+	let mut sum_squared: ExtendedBalance = Zero::zero();
+	for total in totals.values() {
+		sum_squared = sum_squared.saturating_add(total.saturating_mul(*total));
+		if *total < min_support {
+			min_support = *total;
+		}
+	}
+	Ok([min_support, sum, sum_squared])
+}
+
+/// The nakamoto coefficient of a support map: the minimum number of winners whose accumulated
+/// support is more than one third of the total support of all winners.
+///
+/// This is the number of validators that need to collude to halt a BFT consensus. Zero if the total
+/// support is zero.
+pub fn nakamoto_coefficient<AccountId>(support: &SupportMap<AccountId>) -> u32 {
+	let mut totals = support.values().map(|s| s.total).collect::<Vec<_>>();
+	totals.sort_unstable_by(|x, y| y.cmp(x));
+	let total = totals.iter().fold(0 as ExtendedBalance, |acc, t| acc.saturating_add(*t));
+
+	let mut accumulated: ExtendedBalance = Zero::zero();
+	for (i, t) in totals.into_iter().enumerate() {
+		accumulated = accumulated.saturating_add(t);
+		// `accumulated > total / 3`, without rounding.
+		if U256::full_mul(accumulated, 3) > U256::from(total) {
+			return i as u32 + 1
+		}
+	}
+	0
+}
+
 /// Compares two sets of election scores based on desirability and returns true if `this` is better
 /// than `that`.
 ///
@@ -608,6 +1325,204 @@ pub fn is_score_better<P: PerThing>(this: ElectionScore, that: ElectionScore, ep
 	}
 }
 
+/// A minimal unsigned 256 bit integer, only wide enough to hold the sum of squared supports without
+/// saturating.
+///
+/// Fields are ordered such that the derived `Ord` is the numerical order.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct U256 {
+	/// The most significant 128 bits.
+	pub high: u128,
+	/// The least significant 128 bits.
+	pub low: u128,
+}
+
+impl From<u128> for U256 {
+	fn from(low: u128) -> Self {
+		Self { high: 0, low }
+	}
+}
+
+impl U256 {
+	const LOW_MASK: u128 = u64::MAX as u128;
+
+	/// The exact product of two `u128`s.
+	pub fn full_mul(a: u128, b: u128) -> Self {
+		let (a1, a0) = (a >> 64, a & Self::LOW_MASK);
+		let (b1, b0) = (b >> 64, b & Self::LOW_MASK);
+
+		let p00 = a0 * b0;
+		let p01 = a0 * b1;
+		let p10 = a1 * b0;
+		let p11 = a1 * b1;
+
+		// at most 3 * (2^64 - 1), cannot overflow.
+		let mid = (p00 >> 64) + (p01 & Self::LOW_MASK) + (p10 & Self::LOW_MASK);
+		let low = (p00 & Self::LOW_MASK) | (mid << 64);
+		let high = p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64);
+		Self { high, low }
+	}
+
+	/// Saturating addition.
+	pub fn saturating_add(self, other: Self) -> Self {
+		let (low, carry) = self.low.overflowing_add(other.low);
+		match self.high.checked_add(other.high).and_then(|h| h.checked_add(carry as u128)) {
+			Some(high) => Self { high, low },
+			None => Self::max_value(),
+		}
+	}
+
+	/// Saturating subtraction.
+	pub fn saturating_sub(self, other: Self) -> Self {
+		if other >= self {
+			return Self::default()
+		}
+		let (low, borrow) = self.low.overflowing_sub(other.low);
+		// cannot underflow since `self > other`.
+		let high = self.high - other.high - borrow as u128;
+		Self { high, low }
+	}
+
+	/// The largest representable value.
+	pub fn max_value() -> Self {
+		Self { high: u128::MAX, low: u128::MAX }
+	}
+
+	/// Limbs of `self`, sorted as msb -> lsb.
+	fn limbs(self) -> [u64; 4] {
+		[
+			(self.high >> 64) as u64,
+			self.high as u64,
+			(self.low >> 64) as u64,
+			self.low as u64,
+		]
+	}
+
+	fn from_limbs(limbs: [u64; 4]) -> Self {
+		Self {
+			high: (u128::from(limbs[0]) << 64) | u128::from(limbs[1]),
+			low: (u128::from(limbs[2]) << 64) | u128::from(limbs[3]),
+		}
+	}
+
+	/// Divide by a single limb, returning the quotient and the remainder.
+	fn div_rem_limb(self, divisor: u64) -> (Self, u64) {
+		let divisor = u128::from(divisor.max(1));
+		let mut quotient = [0u64; 4];
+		let mut rem: u128 = 0;
+		for (i, limb) in self.limbs().iter().enumerate() {
+			let acc = (rem << 64) | u128::from(*limb);
+			quotient[i] = (acc / divisor) as u64;
+			rem = acc % divisor;
+		}
+		(Self::from_limbs(quotient), rem as u64)
+	}
+
+	/// Saturating multiplication by a single limb.
+	fn saturating_mul_limb(self, multiplier: u64) -> Self {
+		let multiplier = u128::from(multiplier);
+		let mut out = [0u64; 4];
+		let mut carry: u128 = 0;
+		for (i, limb) in self.limbs().iter().enumerate().rev() {
+			let acc = u128::from(*limb) * multiplier + carry;
+			out[i] = acc as u64;
+			carry = acc >> 64;
+		}
+		if carry > 0 {
+			Self::max_value()
+		} else {
+			Self::from_limbs(out)
+		}
+	}
+
+	/// Exact equivalent of [`PerThing::mul_ceil`] for `U256`.
+	///
+	/// All the per-things that exist have an accuracy that fits in a single `u64` limb.
+	fn mul_ceil<P: PerThing>(self, epsilon: P) -> Self
+		where ExtendedBalance: From<InnerOf<P>>
+	{
+		let parts = ExtendedBalance::from(epsilon.deconstruct());
+		let accuracy = ExtendedBalance::from(P::ACCURACY);
+		let (quotient, rem) = self.div_rem_limb(accuracy as u64);
+		// both `rem` and `parts` are less than `accuracy`, so this cannot overflow.
+		let rem_product = rem as ExtendedBalance * parts;
+		let rem_part = rem_product / accuracy + match rem_product % accuracy { 0 => 0, _ => 1 };
+		quotient.saturating_mul_limb(parts as u64).saturating_add(rem_part.into())
+	}
+}
+
+/// An election score in which the sum of squared supports is computed exactly.
+///
+/// The elements are the same as [`ElectionScore`], see [`evaluate_support`].
+pub type ExactElectionScore = (ExtendedBalance, ExtendedBalance, U256);
+
+/// Same as [`evaluate_support`], but the sum of all supports squared is accumulated in a [`U256`]
+/// and thus never saturates.
+///
+/// `O(E)` where `E` is the total number of edges.
+pub fn evaluate_support_exact<AccountId>(
+	support: &SupportMap<AccountId>,
+) -> ExactElectionScore {
+	let mut min_support = ExtendedBalance::max_value();
+	let mut sum: ExtendedBalance = Zero::zero();
+	let mut sum_squared = U256::default();
+	for (_, support) in support.iter() {
+		sum = sum.saturating_add(support.total);
+		sum_squared = sum_squared.saturating_add(U256::full_mul(support.total, support.total));
+		if support.total < min_support {
+			min_support = support.total;
+		}
+	}
+	(min_support, sum, sum_squared)
+}
+
+/// Same as [`is_score_better`], but for scores produced by [`evaluate_support_exact`].
+///
+/// The threshold of the third component is also computed without precision loss.
+pub fn is_exact_score_better<P: PerThing>(
+	this: ExactElectionScore,
+	that: ExactElectionScore,
+	epsilon: P,
+) -> bool
+	where ExtendedBalance: From<sp_arithmetic::InnerOf<P>>
+{
+	let first = this.0.tcmp(&that.0, epsilon.mul_ceil(that.0));
+	let second = this.1.tcmp(&that.1, epsilon.mul_ceil(that.1));
+	let third = {
+		let threshold = that.2.mul_ceil(epsilon);
+		let upper_bound = that.2.saturating_add(threshold);
+		let lower_bound = that.2.saturating_sub(threshold);
+		if threshold == U256::default() || upper_bound <= lower_bound {
+			this.2.cmp(&that.2)
+		} else {
+			match (this.2.cmp(&lower_bound), this.2.cmp(&upper_bound)) {
+				(Ordering::Greater, Ordering::Greater) => Ordering::Greater,
+				(Ordering::Less, Ordering::Less) => Ordering::Less,
+				_ => Ordering::Equal,
+			}
+		}
+	};
+
+	match (
+		(this.0 >= that.0, first),
+		(this.1 >= that.1, second),
+		third,
+	) {
+		// epsilon better in the score[0], accept.
+		((_, Ordering::Greater), _, _) => true,
+
+		// less than epsilon better in score[0], but more than epsilon better in the second.
+		((true, Ordering::Equal), (_, Ordering::Greater), _) => true,
+
+		// less than epsilon better in score[0, 1], but more than epsilon better in the third
+		((true, Ordering::Equal), (true, Ordering::Equal), Ordering::Less) => true,
+
+		// anything else is not a good score.
+		_ => false,
+	}
+}
+
 /// Converts raw inputs to types used in this crate.
 ///
 /// This will perform some cleanup that are most often important:
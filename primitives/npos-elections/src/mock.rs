@@ -19,7 +19,9 @@
 
 #![cfg(test)]
 
-use crate::{seq_phragmen, ElectionResult, Assignment, VoteWeight, ExtendedBalance};
+use crate::{
+	seq_phragmen, ElectionResult, Assignment, VoteWeight, ExtendedBalance, setup_inputs, balance,
+};
 use sp_arithmetic::{PerThing, InnerOf, traits::{SaturatedConversion, Zero, One}};
 use sp_std::collections::btree_map::BTreeMap;
 use sp_runtime::assert_eq_error_rate;
@@ -393,3 +395,75 @@ pub(crate) fn build_support_map_float<FS>(
 	}
 	supports
 }
+
+/// Compute the maximin support of the given `committee`, i.e. the maximum minimum support that any
+/// distribution of the stake of `voters` among the members of the committee can achieve.
+///
+/// A balanced distribution is known to achieve the maximin support, hence we start from an equal
+/// split of each voter's budget and run the balancing algorithm until it converges.
+pub(crate) fn maximin_support_of(
+	committee: &[AccountId],
+	candidates: &[AccountId],
+	voters: &[(AccountId, VoteWeight, Vec<AccountId>)],
+) -> ExtendedBalance {
+	let (candidates, mut voters) = setup_inputs(candidates.to_vec(), voters.to_vec());
+	candidates
+		.iter()
+		.filter(|c| committee.contains(&c.borrow().who))
+		.for_each(|c| c.borrow_mut().elected = true);
+
+	for voter in voters.iter_mut() {
+		voter.edges.retain(|e| e.candidate.borrow().elected);
+		let count = voter.edges.len() as ExtendedBalance;
+		let budget = voter.budget;
+		let edges_len = voter.edges.len();
+		for (index, edge) in voter.edges.iter_mut().enumerate() {
+			edge.weight = if index == edges_len - 1 {
+				budget - (budget / count) * (count - 1)
+			} else {
+				budget / count
+			};
+			let mut candidate = edge.candidate.borrow_mut();
+			candidate.backed_stake = candidate.backed_stake.saturating_add(edge.weight);
+		}
+	}
+
+	balance(&mut voters, 1000, 0);
+
+	candidates
+		.iter()
+		.filter(|c| c.borrow().elected)
+		.map(|c| c.borrow().backed_stake)
+		.min()
+		.unwrap_or(0)
+}
+
+/// Find the committee of size `to_elect` with the highest maximin support, by checking all the
+/// possible committees. Only usable for small inputs.
+pub(crate) fn brute_force_maximin(
+	to_elect: usize,
+	candidates: &[AccountId],
+	voters: &[(AccountId, VoteWeight, Vec<AccountId>)],
+) -> (Vec<AccountId>, ExtendedBalance) {
+	fn committees(candidates: &[AccountId], size: usize) -> Vec<Vec<AccountId>> {
+		if size == 0 {
+			return vec![vec![]];
+		}
+		if candidates.len() < size {
+			return vec![];
+		}
+		let (first, rest) = candidates.split_first().expect("length is at least size > 0; qed");
+		let mut with_first = committees(rest, size - 1);
+		with_first.iter_mut().for_each(|c| c.insert(0, *first));
+		with_first.extend(committees(rest, size));
+		with_first
+	}
+
+	committees(candidates, to_elect)
+		.into_iter()
+		.map(|committee| {
+			let support = maximin_support_of(&committee, candidates, voters);
+			(committee, support)
+		})
+		.fold((vec![], 0), |best, next| if next.1 > best.1 { next } else { best })
+}
@@ -21,7 +21,7 @@ use sp_std::{cell::RefCell, fmt, prelude::*, rc::Rc};
 
 /// The role that a node can accept.
 #[derive(PartialEq, Eq, Ord, PartialOrd, Clone, Debug)]
-pub(crate) enum NodeRole {
+pub enum NodeRole {
 	/// A voter. This is synonym to a nominator in a staking context.
 	Voter,
 	/// A target. This is synonym to a candidate/validator in a staking context.
@@ -34,7 +34,7 @@ pub(crate) type NodeRef<A> = RefCellOf<Node<A>>;
 /// Identifier of a node. This is particularly handy to have a proper `PartialEq` implementation.
 /// Otherwise, self votes wouldn't have been indistinguishable.
 #[derive(PartialOrd, Ord, Clone, PartialEq, Eq)]
-pub(crate) struct NodeId<A> {
+pub struct NodeId<A> {
 	/// An account-like identifier representing the node.
 	pub who: A,
 	/// The role of the node.
@@ -110,11 +110,50 @@ impl<A: PartialEq + Eq + Clone + fmt::Debug> Node<A> {
 	/// Removes the parent of `who`.
 	pub fn remove_parent(who: &NodeRef<A>) {
 		who.borrow_mut().parent = None;
+		#[cfg(feature = "debug-assertions")]
+		Self::assert_invariants(who);
 	}
 
 	/// Sets `who`'s parent to be `parent`.
 	pub fn set_parent_of(who: &NodeRef<A>, parent: &NodeRef<A>) {
+		#[cfg(feature = "debug-assertions")]
+		assert!(
+			!Rc::ptr_eq(who, parent) && who.borrow().id != parent.borrow().id,
+			"a node cannot be its own parent",
+		);
 		who.borrow_mut().parent = Some(parent.clone());
+		#[cfg(feature = "debug-assertions")]
+		Self::assert_invariants(who);
+	}
+
+	/// Check the invariants of the tree that `who` belongs to, panicking if any is broken.
+	///
+	/// Since a node only points to its parent, only the path from `who` to its root is checked:
+	/// - no node is its own parent.
+	/// - no two distinct nodes on the path have the same [`NodeId`].
+	///
+	/// Note that the path might be a cycle, as the reduce algorithm creates transient cycles while
+	/// re-organizing a tree. This is tolerated, similar to [`Node::root`].
+	#[cfg(feature = "debug-assertions")]
+	fn assert_invariants(who: &NodeRef<A>) {
+		let mut visited: Vec<NodeRef<A>> = sp_std::vec![who.clone()];
+		let mut current = who.clone();
+
+		while let Some(ref next_parent) = current.clone().borrow().parent {
+			assert!(
+				!Rc::ptr_eq(next_parent, &current),
+				"a node cannot be its own parent",
+			);
+			if visited.iter().any(|v| Rc::ptr_eq(v, next_parent)) {
+				break;
+			}
+			assert!(
+				visited.iter().all(|v| v.borrow().id != next_parent.borrow().id),
+				"duplicate node in a single tree",
+			);
+			visited.push(next_parent.clone());
+			current = next_parent.clone();
+		}
 	}
 
 	/// Finds the root of `start`. It return a tuple of `(root, root_vec)` where `root_vec` is the
@@ -270,6 +309,27 @@ mod tests {
 		assert_eq!(path.clone(), vec![a.clone(), b.clone(), c.clone()]);
 	}
 
+	#[test]
+	#[cfg(feature = "debug-assertions")]
+	#[should_panic(expected = "cannot be its own parent")]
+	fn self_parent_is_detected() {
+		let a = Node::new(id(1)).into_ref();
+		Node::set_parent_of(&a, &a);
+	}
+
+	#[test]
+	#[cfg(feature = "debug-assertions")]
+	#[should_panic(expected = "duplicate node")]
+	fn duplicate_node_in_tree_is_detected() {
+		// A --> B --> A', where A and A' are distinct nodes with the same id.
+		let a = Node::new(id(1)).into_ref();
+		let b = Node::new(id(2)).into_ref();
+		let a_prime = Node::new(id(1)).into_ref();
+
+		Node::set_parent_of(&b, &a_prime);
+		Node::set_parent_of(&a, &b);
+	}
+
 	#[test]
 	fn node_cmp_stack_overflows_on_non_unique_elements() {
 		// To make sure we don't stack overflow on duplicate who. This needs manual impl of
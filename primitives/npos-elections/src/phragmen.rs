@@ -22,12 +22,13 @@
 
 use crate::{
 	IdentifierT, VoteWeight, Voter, CandidatePtr, ExtendedBalance, setup_inputs, ElectionResult,
+	merge_dust_voters, expand_dust_assignments, internal, Assignment,
 };
-use sp_std::prelude::*;
+use sp_std::{prelude::*, vec, collections::btree_set::BTreeSet};
 use sp_arithmetic::{
-	PerThing, InnerOf, Rational128,
+	PerThing, InnerOf, Rational128, Perbill,
 	helpers_128bit::multiply_by_rational,
-	traits::{Zero, Bounded},
+	traits::{Zero, One, Bounded, IntegerSquareRoot},
 };
 use crate::balancing;
 
@@ -71,12 +72,156 @@ pub fn seq_phragmen<AccountId: IdentifierT, P: PerThing>(
 ) -> Result<ElectionResult<AccountId, P>, &'static str> where ExtendedBalance: From<InnerOf<P>> {
 	let (candidates, voters) = setup_inputs(initial_candidates, initial_voters);
 
-	let (candidates, mut voters) = seq_phragmen_core::<AccountId>(
+	let (candidates, voters) = seq_phragmen_core::<AccountId>(
 		rounds,
 		candidates,
 		voters,
 	)?;
 
+	into_election_result(rounds, candidates, voters, balance)
+}
+
+/// Same as [`seq_phragmen`], but `initial_candidates` are trusted to be sorted by their approval
+/// stake, descending.
+///
+/// In the first round, all voters have zero load and the winner is simply the candidate with the
+/// highest approval stake. Hence, the first one of the sorted candidates is elected right away,
+/// without computing the score of all the others. The outcome is exactly the same as that of
+/// [`seq_phragmen`] if the candidates are indeed sorted, which is only checked in debug builds.
+/// Candidates with equal approval stake should keep the order in which they would be given to
+/// [`seq_phragmen`].
+///
+/// This can only fail if the normalization fails.
+pub fn seq_phragmen_presorted<AccountId: IdentifierT, P: PerThing>(
+	rounds: usize,
+	initial_candidates: Vec<AccountId>,
+	initial_voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+	balance: Option<(usize, ExtendedBalance)>,
+) -> Result<ElectionResult<AccountId, P>, &'static str> where ExtendedBalance: From<InnerOf<P>> {
+	let (candidates, mut voters) = setup_inputs(initial_candidates, initial_voters);
+	debug_assert!(
+		candidates.windows(2).all(|w| w[0].borrow().approval_stake >= w[1].borrow().approval_stake),
+		"candidates must be sorted by approval stake",
+	);
+
+	let to_elect = rounds.min(candidates.len());
+	if to_elect > 0 {
+		let mut winner = candidates[0].borrow_mut();
+		// same as the score computed in the first round of `seq_phragmen_round`.
+		winner.score = if winner.approval_stake.is_zero() {
+			Bounded::max_value()
+		} else {
+			Rational128::from(DEN / winner.approval_stake, DEN)
+		};
+		winner.elected = true;
+		winner.round = 0;
+		for voter in voters.iter_mut() {
+			for edge in &mut voter.edges {
+				if edge.who == winner.who {
+					edge.load = winner.score.lazy_saturating_sub(voter.load);
+					voter.load = winner.score;
+				}
+			}
+		}
+	}
+	for round in 1..to_elect {
+		if !seq_phragmen_round(round, &candidates, &mut voters) {
+			break
+		}
+	}
+	update_backing_stakes(&mut voters)?;
+
+	into_election_result(rounds, candidates, voters, balance)
+}
+
+/// The coefficients used by [`estimate_election_weight_with`] to estimate the weight of
+/// [`seq_phragmen`], in the same unit as the weight system of the runtime.
+///
+/// The [`Default`] values are only placeholders, with one unit of weight being one picosecond of
+/// execution time. A runtime should replace them with the results of its own benchmarks, since
+/// they depend heavily on the hardware and the account id type.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ElectionWeightCoefficients {
+	/// The fixed cost of an election, regardless of its size.
+	pub base: u64,
+	/// The cost of setting up and normalizing each voter.
+	pub per_voter: u64,
+	/// The cost of setting up each candidate.
+	pub per_candidate: u64,
+	/// The cost of setting up each edge, and of computing its final weight.
+	pub per_edge: u64,
+	/// The cost of visiting each edge in each round, to update the scores and loads.
+	pub per_round_edge: u64,
+	/// The cost of visiting each candidate in each round, to find the winner.
+	pub per_round_candidate: u64,
+}
+
+impl Default for ElectionWeightCoefficients {
+	fn default() -> Self {
+		Self {
+			base: 10_000_000,
+			per_voter: 500_000,
+			per_candidate: 200_000,
+			per_edge: 300_000,
+			per_round_edge: 100_000,
+			per_round_candidate: 50_000,
+		}
+	}
+}
+
+/// Estimate the weight of [`seq_phragmen`] without balancing, using the default
+/// [`ElectionWeightCoefficients`].
+///
+/// `edge_count` is the total number of votes of all voters.
+pub fn estimate_election_weight(
+	voter_count: u32,
+	candidate_count: u32,
+	edge_count: u32,
+	to_elect: u32,
+) -> u64 {
+	estimate_election_weight_with(
+		&Default::default(),
+		voter_count,
+		candidate_count,
+		edge_count,
+		to_elect,
+	)
+}
+
+/// Same as [`estimate_election_weight`], with custom coefficients.
+///
+/// Each round of seq-phragmen visits all the candidates and all the edges, hence the estimate is
+/// `O(to_elect ⋅ (edge_count + candidate_count))`. The number of rounds is capped at
+/// `candidate_count`, same as the election itself. The estimate saturates at `u64::max_value()`.
+pub fn estimate_election_weight_with(
+	coefficients: &ElectionWeightCoefficients,
+	voter_count: u32,
+	candidate_count: u32,
+	edge_count: u32,
+	to_elect: u32,
+) -> u64 {
+	let rounds = u64::from(to_elect.min(candidate_count));
+	let (voters, candidates, edges) =
+		(u64::from(voter_count), u64::from(candidate_count), u64::from(edge_count));
+	let round_cost = coefficients.per_round_edge
+		.saturating_mul(edges)
+		.saturating_add(coefficients.per_round_candidate.saturating_mul(candidates));
+
+	coefficients.base
+		.saturating_add(coefficients.per_voter.saturating_mul(voters))
+		.saturating_add(coefficients.per_candidate.saturating_mul(candidates))
+		.saturating_add(coefficients.per_edge.saturating_mul(edges))
+		.saturating_add(round_cost.saturating_mul(rounds))
+}
+
+/// Turn the outcome of [`seq_phragmen_core`] into an [`ElectionResult`], with potentially some
+/// rounds of `balancing`, as described in [`seq_phragmen`].
+fn into_election_result<AccountId: IdentifierT, P: PerThing>(
+	rounds: usize,
+	candidates: Vec<CandidatePtr<AccountId>>,
+	mut voters: Vec<Voter<AccountId>>,
+	balance: Option<(usize, ExtendedBalance)>,
+) -> Result<ElectionResult<AccountId, P>, &'static str> where ExtendedBalance: From<InnerOf<P>> {
 	if let Some((iterations, tolerance)) = balance {
 		// NOTE: might create zero-edges, but we will strip them again when we convert voter into
 		// assignment.
@@ -102,6 +247,567 @@ pub fn seq_phragmen<AccountId: IdentifierT, P: PerThing>(
 	Ok(ElectionResult { winners, assignments })
 }
 
+/// Same as [`seq_phragmen`], but all voters with a stake less than `dust` who vote identically
+/// are first merged into a single voter, as described in [`merge_dust_voters`].
+///
+/// This can drastically reduce the size of the election if there are many small voters. The
+/// merged voters are expanded back in the returned assignments, so the result is compatible with
+/// that of [`seq_phragmen`].
+///
+/// This can only fail if the normalization fails.
+pub fn seq_phragmen_with_dust_merging<AccountId: IdentifierT, P: PerThing>(
+	rounds: usize,
+	initial_candidates: Vec<AccountId>,
+	initial_voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+	balance: Option<(usize, ExtendedBalance)>,
+	dust: VoteWeight,
+) -> Result<ElectionResult<AccountId, P>, &'static str> where ExtendedBalance: From<InnerOf<P>> {
+	let (voters, members) = merge_dust_voters(initial_voters, dust);
+	let ElectionResult { winners, assignments } =
+		seq_phragmen::<AccountId, P>(rounds, initial_candidates, voters, balance)?;
+	let assignments = expand_dust_assignments(assignments, &members);
+
+	Ok(ElectionResult { winners, assignments })
+}
+
+/// Same as [`seq_phragmen`], but balancing, if any, splits the stake of each voter among at most
+/// `max_inner_iterations` of its edges, as in [`balancing::balance_bounded`].
+///
+/// The rounds of seq-phragmen itself distribute the stake of the voters in closed form. The only
+/// part that iterates per voter is balancing, which this bounds for adversarial inputs. With a
+/// high enough `max_inner_iterations`, the outcome is exactly the same as that of
+/// [`seq_phragmen`].
+///
+/// This can only fail if the normalization fails.
+pub fn seq_phragmen_bounded<AccountId: IdentifierT, P: PerThing>(
+	rounds: usize,
+	initial_candidates: Vec<AccountId>,
+	initial_voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+	balance: Option<(usize, ExtendedBalance)>,
+	max_inner_iterations: usize,
+) -> Result<ElectionResult<AccountId, P>, &'static str> where ExtendedBalance: From<InnerOf<P>> {
+	let (candidates, voters) = setup_inputs(initial_candidates, initial_voters);
+	let (candidates, mut voters) = seq_phragmen_core::<AccountId>(rounds, candidates, voters)?;
+
+	if let Some((iterations, tolerance)) = balance {
+		let _iters = balancing::balance_bounded::<AccountId>(
+			&mut voters,
+			iterations,
+			tolerance,
+			max_inner_iterations,
+		);
+	}
+
+	into_election_result(rounds, candidates, voters, None)
+}
+
+/// Same as [`seq_phragmen`], but balancing, if any, is seeded as in [`balancing::balance_shuffled`].
+///
+/// If `record` is provided, all the random draws made by the election are appended to it. This
+/// stream can later be given to [`replay_election`] to reproduce the exact same outcome, without
+/// the seed.
+///
+/// This can only fail if the normalization fails.
+pub fn seq_phragmen_seeded<AccountId: IdentifierT, P: PerThing>(
+	rounds: usize,
+	initial_candidates: Vec<AccountId>,
+	initial_voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+	balance: Option<(usize, ExtendedBalance)>,
+	seed: u64,
+	record: Option<&mut Vec<u32>>,
+) -> Result<ElectionResult<AccountId, P>, &'static str> where ExtendedBalance: From<InnerOf<P>> {
+	let mut rng = internal::SplitMix64::new(seed);
+	match record {
+		Some(record) => seq_phragmen_with_source(
+			rounds,
+			initial_candidates,
+			initial_voters,
+			balance,
+			&mut internal::Recorder { inner: rng, record },
+		),
+		None => seq_phragmen_with_source(
+			rounds,
+			initial_candidates,
+			initial_voters,
+			balance,
+			&mut rng,
+		),
+	}
+}
+
+/// Same as [`seq_phragmen_seeded`], but the random draws are taken from a `stream` that has been
+/// recorded by it, rather than from a seed.
+///
+/// Given the same inputs, the outcome is exactly the same as that of the recorded election. Fails
+/// if the stream is shorter than the number of draws needed by the election. Any extra draws in
+/// the stream are ignored.
+pub fn replay_election<AccountId: IdentifierT, P: PerThing>(
+	rounds: usize,
+	initial_candidates: Vec<AccountId>,
+	initial_voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+	balance: Option<(usize, ExtendedBalance)>,
+	stream: &[u32],
+) -> Result<ElectionResult<AccountId, P>, &'static str> where ExtendedBalance: From<InnerOf<P>> {
+	let mut replay = internal::Replay::new(stream);
+	let result = seq_phragmen_with_source(
+		rounds,
+		initial_candidates,
+		initial_voters,
+		balance,
+		&mut replay,
+	)?;
+	if replay.exhausted {
+		return Err("random stream exhausted");
+	}
+	Ok(result)
+}
+
+/// Same as [`seq_phragmen`], with balancing driven by the given source of random draws.
+fn seq_phragmen_with_source<AccountId: IdentifierT, P: PerThing, R: internal::RandomSource>(
+	rounds: usize,
+	initial_candidates: Vec<AccountId>,
+	initial_voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+	balance: Option<(usize, ExtendedBalance)>,
+	rng: &mut R,
+) -> Result<ElectionResult<AccountId, P>, &'static str> where ExtendedBalance: From<InnerOf<P>> {
+	let (candidates, voters) = setup_inputs(initial_candidates, initial_voters);
+	let (candidates, mut voters) = seq_phragmen_core::<AccountId>(rounds, candidates, voters)?;
+
+	if let Some((iterations, tolerance)) = balance {
+		let _iters = balancing::balance_with_source(&mut voters, iterations, tolerance, rng);
+	}
+	into_election_result(rounds, candidates, voters, None)
+}
+
+/// Same as [`seq_phragmen`], but every winner must be backed by at least `min_backers` distinct
+/// voters.
+///
+/// Winners that end up with fewer backers are demoted and excluded from the candidates, and the
+/// election is executed again, such that their seats are filled by the next best candidates. This
+/// is repeated until all winners have enough backers. Since each repetition excludes at least one
+/// candidate, the election is executed at most `initial_candidates.len()` times. Thus, less than
+/// `rounds` winners are returned if not enough candidates can meet the constraint.
+///
+/// With a `min_backers` of one or less, this is exactly the same as [`seq_phragmen`].
+///
+/// This can only fail if the normalization fails.
+pub fn seq_phragmen_with_min_backers<AccountId: IdentifierT, P: PerThing>(
+	rounds: usize,
+	mut initial_candidates: Vec<AccountId>,
+	initial_voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+	balance: Option<(usize, ExtendedBalance)>,
+	min_backers: usize,
+) -> Result<ElectionResult<AccountId, P>, &'static str> where ExtendedBalance: From<InnerOf<P>> {
+	loop {
+		let result = seq_phragmen::<AccountId, P>(
+			rounds,
+			initial_candidates.clone(),
+			initial_voters.clone(),
+			balance,
+		)?;
+
+		let demoted = result
+			.winners
+			.iter()
+			.map(|(winner, _)| winner)
+			.filter(|winner| {
+				let backers = result
+					.assignments
+					.iter()
+					.filter(|a| a.distribution.iter().any(|(t, _)| t == *winner))
+					.count();
+				backers < min_backers
+			})
+			.cloned()
+			.collect::<Vec<_>>();
+
+		if demoted.is_empty() {
+			return Ok(result);
+		}
+		initial_candidates.retain(|c| !demoted.contains(c));
+	}
+}
+
+/// A part of the stake of a voter that is forcefully moved to a winner, as `(voter, winner, stake)`.
+///
+/// See [`seq_phragmen_with_min_utilization`].
+pub type Reallocation<AccountId> = (AccountId, AccountId, ExtendedBalance);
+
+/// The result of [`seq_phragmen_with_min_utilization`], along with its [`Reallocation`]s.
+pub type UtilizedElectionResult<AccountId, P> =
+	(ElectionResult<AccountId, P>, Vec<Reallocation<AccountId>>);
+
+/// Same as [`seq_phragmen`], but no voter who approves of some of the candidates is left with none
+/// of its stake on a winner.
+///
+/// In [`seq_phragmen`], the whole stake of a voter is distributed among the winners that it
+/// approves of. Thus, only voters who approve solely of losers are wasted. Here, `min_fraction` of
+/// the stake of each such voter is moved to the winner with the lowest backing stake at that
+/// point, which is the one that benefits the most from it. Such voters are processed in the order
+/// of `initial_voters`, and the backing stakes are updated accordingly. Voters with no stake or no
+/// valid votes are left as they are.
+///
+/// Since these voters do not approve of the winner that they are moved to, the moves are returned
+/// as a list of [`Reallocation`]s, next to the result of [`seq_phragmen`], which is left intact.
+/// Hence, all assignments remain normalized and only point at the targets that their voter
+/// approves of.
+///
+/// This can only fail if the normalization fails.
+pub fn seq_phragmen_with_min_utilization<AccountId: IdentifierT, P>(
+	rounds: usize,
+	initial_candidates: Vec<AccountId>,
+	initial_voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+	balance: Option<(usize, ExtendedBalance)>,
+	min_fraction: P,
+) -> Result<UtilizedElectionResult<AccountId, P>, &'static str>
+where
+	ExtendedBalance: From<InnerOf<P>>,
+	P: PerThing + sp_std::ops::Mul<ExtendedBalance, Output = ExtendedBalance>,
+{
+	let result = seq_phragmen::<AccountId, P>(
+		rounds,
+		initial_candidates.clone(),
+		initial_voters.clone(),
+		balance,
+	)?;
+	let mut reallocations = Vec::new();
+	if result.winners.is_empty() || min_fraction.is_zero() {
+		return Ok((result, reallocations));
+	}
+
+	let mut backings = result.winners.clone();
+	for (who, stake, targets) in initial_voters {
+		let wasted = stake > 0
+			&& targets.iter().any(|t| initial_candidates.contains(t))
+			&& !result.assignments.iter().any(|a| a.who == who);
+		if !wasted {
+			continue;
+		}
+		let weakest = backings
+			.iter_mut()
+			.min_by_key(|(_, backing)| *backing)
+			.expect("winners are checked to be non-empty; qed");
+		let moved = min_fraction * (stake as ExtendedBalance);
+		weakest.1 = weakest.1.saturating_add(moved);
+		reallocations.push((who, weakest.0.clone(), moved));
+	}
+
+	Ok((result, reallocations))
+}
+
+/// Same as [`seq_phragmen`], with a soft cap on the support of each candidate, and no balancing.
+///
+/// Unlike a hard cap, a candidate is never excluded because of its support. Instead, the part of
+/// its approval stake that is above `soft_cap` counts only `1 - penalty` towards its score, making
+/// it less attractive to elect. Moreover, once elected, its voters are charged only for the
+/// capped part of its load, which leaves more of their stake to the other candidates that they
+/// approve of. Overall, this yields a flatter distribution of support.
+///
+/// With a zero `penalty`, this is exactly the same as [`seq_phragmen`].
+///
+/// This can only fail if the normalization fails.
+pub fn seq_phragmen_softcap<AccountId: IdentifierT, P: PerThing>(
+	rounds: usize,
+	initial_candidates: Vec<AccountId>,
+	initial_voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+	soft_cap: ExtendedBalance,
+	penalty: Perbill,
+) -> Result<ElectionResult<AccountId, P>, &'static str> where ExtendedBalance: From<InnerOf<P>> {
+	let (candidates, mut voters) = setup_inputs(initial_candidates, initial_voters);
+
+	let to_elect = rounds.min(candidates.len());
+	for round in 0..to_elect {
+		if !seq_phragmen_round_with_soft_cap(
+			round,
+			&candidates,
+			&mut voters,
+			Some((soft_cap, penalty)),
+			&StandardLoad,
+		) {
+			break
+		}
+	}
+	update_backing_stakes(&mut voters)?;
+
+	into_election_result(rounds, candidates, voters, None)
+}
+
+/// Same as [`seq_phragmen`], with quadratic scaling of stakes and no balancing.
+///
+/// The stake of each voter is allocated equally to all of its valid targets, and its effective
+/// stake on each target is the integer square root of the allocated stake, which reduces the
+/// influence of large voters. To this end, each voter backs each of its targets as a separate
+/// voter with the effective stake, thus the load of a voter is not shared among its targets.
+///
+/// The stake of each voter is then split equally among its targets that are elected, hence the
+/// returned assignments are valid ratios of the actual stake. The backing stakes of the returned
+/// winners, however, are in terms of the effective stakes.
+///
+/// This can only fail if the normalization fails.
+pub fn elect_quadratic<AccountId: IdentifierT, P: PerThing>(
+	rounds: usize,
+	initial_candidates: Vec<AccountId>,
+	initial_voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+) -> Result<ElectionResult<AccountId, P>, &'static str> where ExtendedBalance: From<InnerOf<P>> {
+	let candidate_set = initial_candidates.iter().cloned().collect::<BTreeSet<_>>();
+	let mut voters = Vec::with_capacity(initial_voters.len());
+	for (who, stake, targets) in initial_voters {
+		let mut valid = Vec::<AccountId>::with_capacity(targets.len());
+		for target in targets {
+			if candidate_set.contains(&target) && !valid.contains(&target) {
+				valid.push(target);
+			}
+		}
+		let effective = (stake / (valid.len().max(1) as VoteWeight)).integer_sqrt();
+		voters.extend(valid.into_iter().map(|target| (who.clone(), effective, vec![target])));
+	}
+
+	let ElectionResult { winners, assignments } =
+		seq_phragmen::<AccountId, P>(rounds, initial_candidates, voters, None)?;
+
+	// the assignments of the same voter are next to each other, and are merged back into one.
+	let mut merged: Vec<Assignment<AccountId, P>> = Vec::with_capacity(assignments.len());
+	for assignment in assignments {
+		match merged.last_mut() {
+			Some(last) if last.who == assignment.who =>
+				last.distribution.extend(assignment.distribution),
+			_ => merged.push(assignment),
+		}
+	}
+	for assignment in merged.iter_mut() {
+		let count = assignment.distribution.len() as ExtendedBalance;
+		let share = P::from_rational_approximation(1, count);
+		assignment.distribution.iter_mut().for_each(|(_, p)| *p = share);
+		assignment.try_normalize()?;
+	}
+
+	Ok(ElectionResult { winners, assignments: merged })
+}
+
+/// A rule for how the load of a voter affects the score of the candidates that it approves of, in
+/// [`seq_phragmen_with_load_model`].
+///
+/// Loads are fixed point numbers with a denominator of `ExtendedBalance::max_value()`. They are
+/// passed as the [`Rational128`] that seq-phragmen keeps for each voter, rather than as a plain
+/// `ExtendedBalance`, so that no precision is lost in each round and [`StandardLoad`] yields
+/// exactly the same result as [`seq_phragmen`].
+///
+/// [`seq_phragmen`] itself is not generic over the load model: an extra type parameter would
+/// break all of its callers that name its type parameters, e.g. the staking and elections
+/// pallets, hence [`seq_phragmen_with_load_model`] is a separate entry point.
+pub trait LoadModel {
+	/// The effective load of a voter with the given `current` load and `stake`, which is added to
+	/// the score of each candidate that it approves of, proportional to its stake.
+	///
+	/// The higher the effective load of a voter, the less attractive the candidates that it
+	/// approves of become, i.e. this is the marginal cost of electing more of its candidates.
+	fn update_load(&self, current: Rational128, stake: ExtendedBalance) -> Rational128;
+}
+
+/// The standard load rule of seq-phragmen, in which the effective load of a voter is simply its
+/// current load.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct StandardLoad;
+
+impl LoadModel for StandardLoad {
+	fn update_load(&self, current: Rational128, _stake: ExtendedBalance) -> Rational128 {
+		current
+	}
+}
+
+/// Same as [`seq_phragmen`], with a custom [`LoadModel`] and no balancing.
+///
+/// In each round, the score of a candidate is `(1 + sum(load * stake)) / approval_stake`, where the
+/// sum is over all of its voters, and `load` is their effective load as computed by `load_model`.
+/// The candidate with the lowest score is elected, and the load of all of its voters is set to its
+/// score. With [`StandardLoad`], this is exactly the same as [`seq_phragmen`] without balancing.
+///
+/// This can only fail if the normalization fails.
+pub fn seq_phragmen_with_load_model<AccountId: IdentifierT, P: PerThing, L: LoadModel>(
+	rounds: usize,
+	initial_candidates: Vec<AccountId>,
+	initial_voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+	load_model: &L,
+) -> Result<ElectionResult<AccountId, P>, &'static str> where ExtendedBalance: From<InnerOf<P>> {
+	let (candidates, mut voters) = setup_inputs(initial_candidates, initial_voters);
+
+	let to_elect = rounds.min(candidates.len());
+	for round in 0..to_elect {
+		if !seq_phragmen_round_with_soft_cap(round, &candidates, &mut voters, None, load_model) {
+			break
+		}
+	}
+	update_backing_stakes(&mut voters)?;
+
+	into_election_result(rounds, candidates, voters, None)
+}
+
+/// Compute the minimum stake that is needed to enter the set of winners of a seq-phragmen
+/// election, namely the backing stake of the weakest winner.
+///
+/// This is the effective bar that a new candidate would need to pass in order to be elected, given
+/// the same `initial_candidates` and `initial_voters`. No balancing is applied, thus the returned
+/// value is the backing stake computed by [`seq_phragmen_core`]. If no one can be elected, zero is
+/// returned.
+///
+/// This can only fail if the normalization fails.
+pub fn entry_threshold<AccountId: IdentifierT>(
+	initial_candidates: Vec<AccountId>,
+	initial_voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+	rounds: usize,
+) -> Result<ExtendedBalance, &'static str> {
+	let (candidates, voters) = setup_inputs(initial_candidates, initial_voters);
+	let (candidates, _) = seq_phragmen_core::<AccountId>(rounds, candidates, voters)?;
+
+	Ok(candidates
+		.iter()
+		.filter(|c_ptr| c_ptr.borrow().elected)
+		.map(|c_ptr| c_ptr.borrow().backed_stake)
+		.min()
+		.unwrap_or_else(Zero::zero))
+}
+
+/// Compute the price of each seat of a seq-phragmen election, namely the load at which each of
+/// the winners has been elected.
+///
+/// Following the load interpretation of phragmen, electing a winner costs one unit of load, which
+/// is spread among its voters. The more loaded the voters of a winner already are, the higher is
+/// the load at which it is elected, i.e. the more expensive its seat is. Winners are returned in
+/// the order in which they are elected and, in seq-phragmen, the price of each seat is never less
+/// than that of the previous one.
+///
+/// The arguments are the same as [`entry_threshold`]. Loads are returned as fixed point numbers
+/// with a denominator of `ExtendedBalance::max_value()`, saturating at one. This is only possible
+/// if the stakes are very small.
+///
+/// This can only fail if the normalization fails.
+pub fn seat_prices<AccountId: IdentifierT>(
+	initial_candidates: Vec<AccountId>,
+	initial_voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+	rounds: usize,
+) -> Result<Vec<(AccountId, ExtendedBalance)>, &'static str> {
+	let (candidates, voters) = setup_inputs(initial_candidates, initial_voters);
+	let (mut candidates, _) = seq_phragmen_core::<AccountId>(rounds, candidates, voters)?;
+
+	candidates.retain(|c_ptr| c_ptr.borrow().elected);
+	candidates.sort_by_key(|c_ptr| c_ptr.borrow().round);
+	Ok(candidates
+		.into_iter()
+		.map(|c_ptr| {
+			let candidate = c_ptr.borrow();
+			let price = multiply_by_rational(candidate.score.n(), DEN, candidate.score.d())
+				.unwrap_or(DEN);
+			(candidate.who.clone(), price)
+		})
+		.collect())
+}
+
+/// Rank all the candidates by their approval stake, namely the sum of the stakes of all the voters
+/// who approve of them, in descending order.
+///
+/// This is computed before any of the rounds of seq-phragmen are executed, and is merely a
+/// baseline to which the actual winners can be compared. Candidates with equal approval stake keep
+/// their order in `initial_candidates`. Unlike the winners of seq-phragmen, all candidates are
+/// returned, even those with no approval stake.
+pub fn approval_ranking<AccountId: IdentifierT>(
+	initial_candidates: Vec<AccountId>,
+	initial_voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+) -> Vec<(AccountId, ExtendedBalance)> {
+	let (candidates, _) = setup_inputs(initial_candidates, initial_voters);
+
+	let mut ranking = candidates
+		.into_iter()
+		.map(|c_ptr| {
+			let candidate = c_ptr.borrow();
+			(candidate.who.clone(), candidate.approval_stake)
+		})
+		.collect::<Vec<_>>();
+	ranking.sort_by(|(_, a), (_, b)| b.cmp(a));
+	ranking
+}
+
+/// The state of a seq-phragmen election that can be executed in multiple steps.
+///
+/// This is useful if the election needs to be spread over multiple blocks, e.g. to fit within the
+/// weight budget of each of them. See [`start_election`], [`step`] and [`finalize`].
+pub struct ElectionState<AccountId> {
+	/// The candidates of the election.
+	candidates: Vec<CandidatePtr<AccountId>>,
+	/// The voters of the election.
+	voters: Vec<Voter<AccountId>>,
+	/// The number of winners that are desired.
+	rounds: usize,
+	/// The index of the next round to be executed.
+	round: usize,
+	/// True if the election is over, i.e. no more rounds need to be executed.
+	done: bool,
+	/// The balancing config, applied upon [`finalize`].
+	balance: Option<(usize, ExtendedBalance)>,
+}
+
+impl<AccountId> ElectionState<AccountId> {
+	/// True if all the rounds of the election have been executed.
+	pub fn is_done(&self) -> bool {
+		self.done
+	}
+
+	/// The number of rounds that have been executed so far.
+	pub fn rounds_executed(&self) -> usize {
+		self.round
+	}
+}
+
+/// Start a seq-phragmen election, without executing any of its rounds.
+///
+/// The arguments are the same as [`seq_phragmen`]. The returned state should be advanced with
+/// [`step`] until it is done, and then be turned into an [`ElectionResult`] with [`finalize`].
+pub fn start_election<AccountId: IdentifierT>(
+	rounds: usize,
+	initial_candidates: Vec<AccountId>,
+	initial_voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>,
+	balance: Option<(usize, ExtendedBalance)>,
+) -> ElectionState<AccountId> {
+	let (candidates, voters) = setup_inputs(initial_candidates, initial_voters);
+	let done = rounds.min(candidates.len()).is_zero();
+	ElectionState { candidates, voters, rounds, round: 0, done, balance }
+}
+
+/// Execute at most `max_rounds` rounds of the given election.
+///
+/// Returns true if the election is done, after which calling this function again is a noop.
+pub fn step<AccountId: IdentifierT>(state: &mut ElectionState<AccountId>, max_rounds: usize) -> bool {
+	let to_elect = state.rounds.min(state.candidates.len());
+	let mut executed = 0;
+	while !state.done && executed < max_rounds {
+		if seq_phragmen_round(state.round, &state.candidates, &mut state.voters) {
+			state.round += 1;
+		} else {
+			state.done = true;
+		}
+		executed += 1;
+		if state.round >= to_elect {
+			state.done = true;
+		}
+	}
+	state.done
+}
+
+/// Finalize the given election and build its result.
+///
+/// If the election is not yet done, the remaining rounds are all executed first. The outcome is
+/// then exactly the same as that of [`seq_phragmen`] with the same arguments.
+///
+/// This can only fail if the normalization fails.
+pub fn finalize<AccountId: IdentifierT, P: PerThing>(
+	mut state: ElectionState<AccountId>,
+) -> Result<ElectionResult<AccountId, P>, &'static str> where ExtendedBalance: From<InnerOf<P>> {
+	let remaining = state.rounds;
+	step(&mut state, remaining);
+	let ElectionState { candidates, mut voters, rounds, balance, .. } = state;
+	update_backing_stakes(&mut voters)?;
+	into_election_result(rounds, candidates, voters, balance)
+}
+
 /// Core implementation of seq-phragmen.
 ///
 /// This is the internal implementation that works with the types defined in this crate. see
@@ -121,48 +827,107 @@ pub fn seq_phragmen_core<AccountId: IdentifierT>(
 
 	// main election loop
 	for round in 0..to_elect {
-		// loop 1: initialize score
-		for c_ptr in &candidates {
-			let mut candidate = c_ptr.borrow_mut();
-			if !candidate.elected {
-				// 1 / approval_stake == (DEN / approval_stake) / DEN. If approval_stake is zero,
-				// then the ratio should be as large as possible, essentially `infinity`.
-				if candidate.approval_stake.is_zero() {
-					candidate.score = Bounded::max_value();
-				} else {
-					candidate.score = Rational128::from(DEN / candidate.approval_stake, DEN);
-				}
+		if !seq_phragmen_round(round, &candidates, &mut voters) {
+			break
+		}
+	}
+
+	update_backing_stakes(&mut voters)?;
+
+	Ok((candidates, voters))
+}
+
+/// Execute a single round of seq-phragmen, electing the `round`-th winner.
+///
+/// Returns `false` if no one could be elected in this round, i.e. everyone is already elected.
+fn seq_phragmen_round<AccountId: IdentifierT>(
+	round: usize,
+	candidates: &[CandidatePtr<AccountId>],
+	voters: &mut [Voter<AccountId>],
+) -> bool {
+	seq_phragmen_round_with_soft_cap(round, candidates, voters, None, &StandardLoad)
+}
+
+/// The approval stake of a candidate that counts towards its score, given a `soft_cap`.
+///
+/// The part of `approval_stake` above the soft cap is discounted by the penalty. This never drops
+/// to zero for a non-zero approval stake, since that would forbid electing the candidate.
+fn effective_approval_stake(
+	approval_stake: ExtendedBalance,
+	soft_cap: Option<(ExtendedBalance, Perbill)>,
+) -> ExtendedBalance {
+	match soft_cap {
+		Some((cap, penalty)) if approval_stake > cap => {
+			let kept = Perbill::from_parts(Perbill::ACCURACY - penalty.deconstruct());
+			cap.saturating_add(kept * (approval_stake - cap)).max(One::one())
+		},
+		_ => approval_stake,
+	}
+}
+
+/// Scale the given rational by `num / den`. Saturates if the numerator does not fit.
+fn scale_rational(r: Rational128, num: ExtendedBalance, den: ExtendedBalance) -> Rational128 {
+	let n = multiply_by_rational(r.n(), num, den).unwrap_or(Bounded::max_value());
+	Rational128::from(n, r.d())
+}
+
+/// Same as [`seq_phragmen_round`], with an optional soft cap, as described in
+/// [`seq_phragmen_softcap`], and the effective loads of the voters computed by `load_model`, as
+/// described in [`seq_phragmen_with_load_model`].
+fn seq_phragmen_round_with_soft_cap<AccountId: IdentifierT, L: LoadModel>(
+	round: usize,
+	candidates: &[CandidatePtr<AccountId>],
+	voters: &mut [Voter<AccountId>],
+	soft_cap: Option<(ExtendedBalance, Perbill)>,
+	load_model: &L,
+) -> bool {
+	// loop 1: initialize score
+	for c_ptr in candidates {
+		let mut candidate = c_ptr.borrow_mut();
+		if !candidate.elected {
+			let approval_stake = effective_approval_stake(candidate.approval_stake, soft_cap);
+			// 1 / approval_stake == (DEN / approval_stake) / DEN. If approval_stake is zero,
+			// then the ratio should be as large as possible, essentially `infinity`.
+			if approval_stake.is_zero() {
+				candidate.score = Bounded::max_value();
+			} else {
+				candidate.score = Rational128::from(DEN / approval_stake, DEN);
 			}
 		}
+	}
 
-		// loop 2: increment score
-		for voter in &voters {
-			for edge in &voter.edges {
-				let mut candidate = edge.candidate.borrow_mut();
-				if !candidate.elected && !candidate.approval_stake.is_zero() {
-					let temp_n = multiply_by_rational(
-						voter.load.n(),
-						voter.budget,
-						candidate.approval_stake,
-					).unwrap_or(Bounded::max_value());
-					let temp_d = voter.load.d();
-					let temp = Rational128::from(temp_n, temp_d);
-					candidate.score = candidate.score.lazy_saturating_add(temp);
-				}
+	// loop 2: increment score
+	for voter in voters.iter() {
+		let load = load_model.update_load(voter.load, voter.budget);
+		for edge in &voter.edges {
+			let mut candidate = edge.candidate.borrow_mut();
+			if !candidate.elected && !candidate.approval_stake.is_zero() {
+				let temp_n = multiply_by_rational(
+					load.n(),
+					voter.budget,
+					effective_approval_stake(candidate.approval_stake, soft_cap),
+				).unwrap_or(Bounded::max_value());
+				let temp_d = load.d();
+				let temp = Rational128::from(temp_n, temp_d);
+				candidate.score = candidate.score.lazy_saturating_add(temp);
 			}
 		}
+	}
 
-		// loop 3: find the best
-		if let Some(winner_ptr) = candidates
-			.iter()
-			.filter(|c| !c.borrow().elected)
-			.min_by_key(|c| c.borrow().score)
-		{
-			let mut winner = winner_ptr.borrow_mut();
-			// loop 3: update voter and edge load
-			winner.elected = true;
-			winner.round = round;
-			for voter in &mut voters {
+	// loop 3: find the best
+	if let Some(winner_ptr) = candidates
+		.iter()
+		.filter(|c| !c.borrow().elected)
+		.min_by_key(|c| c.borrow().score)
+	{
+		let mut winner = winner_ptr.borrow_mut();
+		// loop 3: update voter and edge load
+		winner.elected = true;
+		winner.round = round;
+		let approval_stake = winner.approval_stake;
+		let effective = effective_approval_stake(approval_stake, soft_cap);
+		if effective == approval_stake {
+			for voter in voters.iter_mut() {
 				for edge in &mut voter.edges {
 					if edge.who == winner.who {
 						edge.load = winner.score.lazy_saturating_sub(voter.load);
@@ -171,12 +936,37 @@ pub fn seq_phragmen_core<AccountId: IdentifierT>(
 				}
 			}
 		} else {
-			break
+			// the score that the winner would have without the soft cap.
+			let score = scale_rational(winner.score, effective, approval_stake);
+			for voter in voters.iter_mut() {
+				for edge in &mut voter.edges {
+					if edge.who == winner.who {
+						// voters are only charged for the capped part of the load.
+						edge.load = scale_rational(
+							score.lazy_saturating_sub(voter.load),
+							effective,
+							approval_stake,
+						);
+						voter.load = voter.load.lazy_saturating_add(edge.load);
+					}
+				}
+			}
 		}
+		true
+	} else {
+		false
 	}
+}
 
+/// Compute the final edge weights and backing stake of all voters and candidates, after all the
+/// rounds of seq-phragmen are executed.
+///
+/// This can only fail if the normalization fails.
+fn update_backing_stakes<AccountId: IdentifierT>(
+	voters: &mut [Voter<AccountId>],
+) -> Result<(), &'static str> {
 	// update backing stake of candidates and voters
-	for voter in &mut voters {
+	for voter in voters.iter_mut() {
 		for edge in &mut voter.edges {
 			if edge.candidate.borrow().elected {
 				// update internal state.
@@ -202,5 +992,5 @@ pub fn seq_phragmen_core<AccountId: IdentifierT>(
 		voter.try_normalize_elected()?;
 	}
 
-	Ok((candidates, voters))
+	Ok(())
 }
@@ -48,7 +48,7 @@
 //! 1. https://hackmd.io/JOn9x98iS0e0DPWQ87zGWg?view
 
 use crate::node::{Node, NodeId, NodeRef, NodeRole};
-use crate::{ExtendedBalance, IdentifierT, StakedAssignment};
+use crate::{Error, ExtendedBalance, IdentifierT, StakedAssignment, internal};
 use sp_arithmetic::traits::{Bounded, Zero};
 use sp_std::{
 	collections::btree_map::{BTreeMap, Entry::*},
@@ -59,6 +59,9 @@ use sp_std::{
 /// Map type used for reduce_4. Can be easily swapped with HashMap.
 type Map<A> = BTreeMap<(A, A), A>;
 
+/// A candidate edge to be removed from a cycle in `reduce_all`.
+type CycleEdge<A> = (ExtendedBalance, usize, usize, u32, A, A);
+
 /// Returns all combinations of size two in the collection `input` with no repetition.
 fn combinations_2<T: Clone>(input: &[T]) -> Vec<(T, T)> {
 	let n = input.len();
@@ -105,7 +108,7 @@ fn merge<A: IdentifierT>(voter_root_path: Vec<NodeRef<A>>, target_root_path: Vec
 /// result will most likely be corrupt otherwise.
 ///
 /// O(|E_w| ⋅ k).
-fn reduce_4<A: IdentifierT>(assignments: &mut Vec<StakedAssignment<A>>) -> u32 {
+fn reduce_4<A: IdentifierT>(assignments: &mut [StakedAssignment<A>]) -> u32 {
 	let mut combination_map: Map<A> = Map::new();
 	let mut num_changed: u32 = Zero::zero();
 
@@ -329,9 +332,24 @@ fn reduce_4<A: IdentifierT>(assignments: &mut Vec<StakedAssignment<A>>) -> u32 {
 /// result will most likely be corrupt otherwise.
 ///
 /// O(|Ew| ⋅ m)
-fn reduce_all<A: IdentifierT>(assignments: &mut Vec<StakedAssignment<A>>) -> u32 {
+fn reduce_all<A: IdentifierT, F>(assignments: &mut [StakedAssignment<A>], priority_of: F) -> u32
+where
+	F: Fn(&A, &A) -> u8,
+{
+	reduce_all_with_tree(assignments, priority_of, &mut BTreeMap::new())
+}
+
+/// Same as [`reduce_all`], but the forest that is built along the way is kept in `tree`, which is
+/// expected to be empty.
+fn reduce_all_with_tree<A: IdentifierT, F>(
+	assignments: &mut [StakedAssignment<A>],
+	priority_of: F,
+	tree: &mut BTreeMap<NodeId<A>, NodeRef<A>>,
+) -> u32
+where
+	F: Fn(&A, &A) -> u8,
+{
 	let mut num_changed: u32 = Zero::zero();
-	let mut tree: BTreeMap<NodeId<A>, NodeRef<A>> = BTreeMap::new();
 
 	// NOTE: This code can heavily use an index cache. Looking up a pair of (voter, target) in the
 	// assignments happens numerous times and and we can save time. For now it is written as such
@@ -455,37 +473,57 @@ fn reduce_all<A: IdentifierT>(assignments: &mut Vec<StakedAssignment<A>>) -> u32
 						cycle.len() - 1
 					}
 				};
+
+				// The edges of the cycle alternate between two classes, and removing a circulation
+				// zeroes the minimum of exactly one of them. Keep the minimum of each class, as
+				// `(weight, order of visit, index, direction, voter, target)`.
+				let mut class_min: [Option<CycleEdge<A>>; 2] = [None, None];
+				let mut visited = 0usize;
 				for i in 0..cycle.len() {
 					if cycle[i].borrow().id.role == NodeRole::Voter {
 						// NOTE: sadly way too many clones since I don't want to make A: Copy
 						let current = cycle[i].borrow().id.who.clone();
-						let next = cycle[next_index(i)].borrow().id.who.clone();
-						let prev = cycle[prev_index(i)].borrow().id.who.clone();
-						assignments.iter().find(|a| a.who == current).map(|ass| {
-							ass.distribution.iter().find(|d| d.0 == next).map(|(_, w)| {
-								if *w < min_value {
-									min_value = *w;
-									min_target = next.clone();
-									min_voter = current.clone();
-									min_index = i;
-									min_direction = 1;
+						for &(other_index, edge_index, direction) in
+							[(next_index(i), i, 1u32), (prev_index(i), prev_index(i), 0)].iter()
+						{
+							let other = cycle[other_index].borrow().id.who.clone();
+							let maybe_weight = assignments
+								.iter()
+								.find(|a| a.who == current)
+								.and_then(|ass| ass.distribution.iter().find(|d| d.0 == other))
+								.map(|(_, w)| *w);
+							if let Some(w) = maybe_weight {
+								let class = &mut class_min[edge_index % 2];
+								let is_better = match class {
+									Some(c) => w < c.0,
+									None => true,
+								};
+								if is_better {
+									*class = Some((w, visited, i, direction, current.clone(), other));
 								}
-							})
-						});
-						assignments.iter().find(|a| a.who == current).map(|ass| {
-							ass.distribution.iter().find(|d| d.0 == prev).map(|(_, w)| {
-								if *w < min_value {
-									min_value = *w;
-									min_target = prev.clone();
-									min_voter = current.clone();
-									min_index = i;
-									min_direction = 0;
-								}
-							})
-						});
+								visited += 1;
+							}
+						}
 					}
 				}
 
+				// Among the two candidates, remove the one with the lowest priority. With equal
+				// priorities, this is the minimum of the entire cycle.
+				if let Some((w, _, i, direction, voter, target)) = class_min
+					.iter()
+					.flatten()
+					.min_by_key(|(w, visited, _, _, voter, target)| {
+						(priority_of(voter, target), *w, *visited)
+					})
+					.cloned()
+				{
+					min_value = w;
+					min_target = target;
+					min_voter = voter;
+					min_index = i;
+					min_direction = direction;
+				}
+
 				// if the min edge is in the voter's sub-chain.
 				// [target, ..., X, Y, ... voter]
 				let target_chunk = target_root_path.len() - common_count;
@@ -641,6 +679,56 @@ fn reduce_all<A: IdentifierT>(assignments: &mut Vec<StakedAssignment<A>>) -> u32
 	num_changed
 }
 
+/// Execute `f` over `assignments` in their canonical order, namely with the voters sorted by id and
+/// the distribution of each voter sorted by target. The order of the voters, and that of the
+/// targets of each voter, is restored afterwards.
+///
+/// This makes the edges that are kept by a reduction independent of the order of the input.
+fn in_canonical_order<A: IdentifierT, R, F>(assignments: &mut [StakedAssignment<A>], f: F) -> R
+where
+	F: FnOnce(&mut [StakedAssignment<A>]) -> R,
+{
+	let original = assignments
+		.iter()
+		.map(|a| {
+			let targets = a.distribution.iter().map(|(t, _)| t.clone()).collect::<Vec<_>>();
+			(a.who.clone(), targets)
+		})
+		.collect::<Vec<_>>();
+	let mut position_of = BTreeMap::new();
+	for (index, (who, _)) in original.iter().enumerate() {
+		position_of.entry(who.clone()).or_insert(index);
+	}
+
+	internal::stable_sort_by_id(assignments, |a| &a.who);
+	for assignment in assignments.iter_mut() {
+		internal::stable_sort_by_id(&mut assignment.distribution, |(t, _)| t);
+	}
+
+	let outcome = f(assignments);
+
+	let position = |who: &A| *position_of.get(who).expect("all voters are indexed above; qed");
+	assignments.sort_by_key(|a| position(&a.who));
+	for assignment in assignments.iter_mut() {
+		// the n-th edge to a target goes back to the position of the n-th original one, which
+		// keeps duplicate targets in order as well.
+		let targets = &original[position(&assignment.who)].1;
+		let mut occurrences = BTreeMap::<A, usize>::new();
+		let mut keyed = sp_std::mem::take(&mut assignment.distribution)
+			.into_iter()
+			.map(|(t, w)| {
+				let nth = occurrences.entry(t.clone()).or_insert(0);
+				let key = targets.iter().enumerate().filter(|(_, x)| **x == t).nth(*nth);
+				*nth += 1;
+				(key.map(|(index, _)| index), (t, w))
+			})
+			.collect::<Vec<_>>();
+		keyed.sort_by_key(|(key, _)| *key);
+		assignment.distribution = keyed.into_iter().map(|(_, edge)| edge).collect();
+	}
+	outcome
+}
+
 /// Reduce the given [`Vec<StakedAssignment<IdentifierT>>`]. This removes redundant edges from
 /// without changing the overall backing of any of the elected candidates.
 ///
@@ -650,13 +738,305 @@ fn reduce_all<A: IdentifierT>(assignments: &mut Vec<StakedAssignment<A>>) -> u32
 /// unique. The result will most likely be corrupt otherwise. Furthermore, if the _distribution
 /// vector_ contains duplicate ids, only the first instance is ever updates.
 ///
+/// The edges that are kept do not depend on the order of the assignments, nor on the order of
+/// the targets of each assignment, and both orders are preserved.
+///
 /// O(min{ |Ew| ⋅ k + m3 , |Ew| ⋅ m })
-pub fn reduce<A: IdentifierT>(assignments: &mut Vec<StakedAssignment<A>>) -> u32 where {
-	let mut num_changed = reduce_4(assignments);
-	num_changed += reduce_all(assignments);
+pub fn reduce<A: IdentifierT>(assignments: &mut [StakedAssignment<A>]) -> u32 where {
+	in_canonical_order(assignments, |assignments| {
+		let mut num_changed = reduce_4(assignments);
+		num_changed += reduce_all(assignments, |_, _| 0);
+		num_changed
+	})
+}
+
+/// A spanning forest of the assignment graph, as a list of `(child, parent)` edges.
+pub type Forest<A> = Vec<(NodeId<A>, NodeId<A>)>;
+
+/// Same as [`reduce`], but the spanning forest that the reduction ends up with is returned as
+/// well, as a list of `(child, parent)` edges sorted by child.
+///
+/// Each edge of the forest is an edge of the reduced assignments, from a voter to a target or
+/// vice versa. The forest spans all the nodes of the reduced assignments, and roots are the nodes
+/// that have no parent, hence they never appear as a child. This is mostly useful for debugging,
+/// and to visualize how the graph is organized by the reduction.
+///
+/// The forest is checked to be acyclic, and [`Error::CyclicForest`] is returned otherwise. The
+/// assignments are reduced nonetheless.
+pub fn reduce_with_forest<A: IdentifierT>(
+	assignments: &mut [StakedAssignment<A>],
+) -> Result<(u32, Forest<A>), Error> {
+	let mut tree = BTreeMap::new();
+	let num_changed = in_canonical_order(assignments, |assignments| {
+		reduce_4(assignments) + reduce_all_with_tree(assignments, |_, _| 0, &mut tree)
+	});
+
+	// the walk towards the root of a node only stops early if it would visit a node twice.
+	if !tree.values().all(|node| Node::root(node).0.borrow().parent.is_none()) {
+		return Err(Error::CyclicForest);
+	}
+
+	let forest = tree
+		.iter()
+		.filter_map(|(id, node)| {
+			node.borrow().parent.as_ref().map(|parent| (id.clone(), parent.borrow().id.clone()))
+		})
+		.collect();
+	Ok((num_changed, forest))
+}
+
+/// Same as [`reduce`], but whenever there is a choice of which edge of a cycle to remove, the one
+/// with the lower `priority_of(voter, target)` is removed and higher priority edges are preserved.
+///
+/// Returns the number of edges removed. The backing of all candidates is preserved, exactly as
+/// with [`reduce`].
+///
+/// The `reduce_4` pre-computation is skipped, since it does not look at priorities. Hence, this is
+/// `O(|Ew| ⋅ m)`.
+pub fn reduce_prioritized<A: IdentifierT, F>(
+	assignments: &mut [StakedAssignment<A>],
+	priority_of: F,
+) -> u32
+where
+	F: Fn(&A, &A) -> u8,
+{
+	in_canonical_order(assignments, |assignments| reduce_all(assignments, priority_of))
+}
+
+/// Split the given assignments into the connected components of the graph of their edges.
+///
+/// Returns the indices of the assignments of each component. The indices of each component are
+/// sorted, and components are sorted by their first index.
+#[cfg(feature = "parallel")]
+fn connected_components<A: IdentifierT>(assignments: &[StakedAssignment<A>]) -> Vec<Vec<usize>> {
+	fn find(parents: &mut [usize], mut x: usize) -> usize {
+		while parents[x] != x {
+			parents[x] = parents[parents[x]];
+			x = parents[x];
+		}
+		x
+	}
+
+	// every target is mapped to the index of the first assignment that votes for it. This is
+	// enough to union all the voters who share a target.
+	let mut parents = (0..assignments.len()).collect::<Vec<_>>();
+	let mut first_voter_of = BTreeMap::<A, usize>::new();
+	for (index, assignment) in assignments.iter().enumerate() {
+		for (target, _) in assignment.distribution.iter() {
+			let other = *first_voter_of.entry(target.clone()).or_insert(index);
+			let (root, other_root) = (find(&mut parents, index), find(&mut parents, other));
+			if root != other_root {
+				parents[root.max(other_root)] = root.min(other_root);
+			}
+		}
+	}
+
+	let mut components: Vec<Vec<usize>> = Vec::new();
+	let mut component_of = BTreeMap::<usize, usize>::new();
+	for index in 0..assignments.len() {
+		let root = find(&mut parents, index);
+		match component_of.get(&root) {
+			Some(component) => components[*component].push(index),
+			None => {
+				component_of.insert(root, components.len());
+				components.push(vec![index]);
+			},
+		}
+	}
+	components
+}
+
+/// Same as [`reduce`], but the connected components of the graph of the edges of `assignments`
+/// are reduced on a bounded number of worker threads.
+///
+/// Since cycles never span multiple components, the outcome is exactly the same as that of
+/// [`reduce`]. Components with a single voter cannot contain any cycles, and are reduced on the
+/// current thread. The rest are dealt round-robin to at most `workers` threads, which is treated
+/// as one if zero.
+///
+/// Returns the number of edges removed.
+#[cfg(feature = "parallel")]
+pub fn reduce_parallel<A>(assignments: &mut Vec<StakedAssignment<A>>, workers: usize) -> u32
+where
+	A: IdentifierT + Send + 'static,
+{
+	let components = connected_components(assignments);
+	let mut slots = sp_std::mem::take(assignments).into_iter().map(Some).collect::<Vec<_>>();
+
+	let workers = workers.max(1);
+	let mut batches = (0..workers).map(|_| Vec::new()).collect::<Vec<_>>();
+	let mut num_changed = 0;
+	let mut next = 0;
+	for component in components {
+		let mut part = component
+			.iter()
+			.map(|index| slots[*index].take().expect("each index is in exactly one component; qed"))
+			.collect::<Vec<_>>();
+		if part.len() > 1 {
+			batches[next % workers].push((component, part));
+			next += 1;
+		} else {
+			num_changed += reduce(&mut part);
+			slots[component[0]] = part.pop();
+		}
+	}
+
+	let handles = batches
+		.into_iter()
+		.filter(|batch| !batch.is_empty())
+		.map(|mut batch| {
+			std::thread::spawn(move || {
+				let changed = batch.iter_mut().map(|(_, part)| reduce(part)).sum::<u32>();
+				(batch, changed)
+			})
+		})
+		.collect::<Vec<_>>();
+
+	for handle in handles {
+		let (batch, changed) = handle
+			.join()
+			.unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+		num_changed += changed;
+		for (component, part) in batch {
+			for (index, assignment) in component.into_iter().zip(part) {
+				slots[index] = Some(assignment);
+			}
+		}
+	}
+
+	*assignments = slots
+		.into_iter()
+		.map(|slot| slot.expect("all components are put back; qed"))
+		.collect();
 	num_changed
 }
 
+/// Compute the minimum number of edges needed to represent the given assignments, namely the
+/// number of voters plus the number of targets minus the number of connected components of the
+/// graph of their edges.
+///
+/// A solution without any cycles is a forest, and has exactly this many edges. Hence, this is the
+/// edge count that [`reduce`] achieves if removing the cycles does not split any of the components
+/// further, and callers can use it to tell how close their reduced solution is to optimal.
+///
+/// Same as [`reduce`], voters and targets are distinct nodes, even if they share the same id.
+pub fn min_edges_bound<A: IdentifierT>(assignments: &[StakedAssignment<A>]) -> usize {
+	let mut tree: BTreeMap<NodeId<A>, NodeRef<A>> = BTreeMap::new();
+
+	for StakedAssignment { who, distribution } in assignments {
+		let voter_id = NodeId::from(who.clone(), NodeRole::Voter);
+		let voter_node = tree
+			.entry(voter_id.clone())
+			.or_insert_with(|| Node::new(voter_id).into_ref())
+			.clone();
+		for (target, _) in distribution {
+			let target_id = NodeId::from(target.clone(), NodeRole::Target);
+			let target_node = tree
+				.entry(target_id.clone())
+				.or_insert_with(|| Node::new(target_id).into_ref())
+				.clone();
+
+			let (voter_root, voter_root_path) = Node::root(&voter_node);
+			let (target_root, target_root_path) = Node::root(&target_node);
+			if voter_root != target_root {
+				merge(voter_root_path, target_root_path);
+			}
+		}
+	}
+
+	let components = tree.values().filter(|node| node.borrow().parent.is_none()).count();
+	tree.len() - components
+}
+
+/// A single step of a reduce witness, namely a circulation that moves stake among some of the
+/// edges of a solution.
+///
+/// Such a step never changes the budget of a voter or the support of a target. Some of the edges
+/// might drop to zero in the process, and are then removed from the solution.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReduceStep<A> {
+	/// The `(voter, target, stake)` edges that lose stake.
+	pub decrease: Vec<(A, A, ExtendedBalance)>,
+	/// The `(voter, target, stake)` edges that gain stake.
+	pub increase: Vec<(A, A, ExtendedBalance)>,
+}
+
+/// The errors that might occur while verifying a [`ReduceStep`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+	/// The step refers to an edge that does not exist in the solution. A reduce step can only
+	/// remove edges, never add new ones.
+	UnknownEdge,
+	/// The step removes more stake from an edge than it has.
+	EdgeUnderflow,
+	/// The step changes the budget of a voter.
+	BudgetChanged,
+	/// The step changes the support of a target.
+	SupportChanged,
+}
+
+/// Find the stake of the edge from `voter` to `target` in `state`.
+fn edge_stake_mut<'a, A: IdentifierT>(
+	state: &'a mut [StakedAssignment<A>],
+	voter: &A,
+	target: &A,
+) -> Result<&'a mut ExtendedBalance, VerifyError> {
+	state
+		.iter_mut()
+		.find(|a| a.who == *voter)
+		.and_then(|a| a.distribution.iter_mut().find(|(t, _)| t == target))
+		.map(|(_, stake)| stake)
+		.ok_or(VerifyError::UnknownEdge)
+}
+
+/// Verify and apply a single step of a reduce witness to `state`, returning the new state.
+///
+/// The step must only move stake among the existing edges of `state`, and must preserve the
+/// budget of all voters and the support of all targets. Edges whose stake drops to zero are
+/// removed. This allows a verifier to process a witness incrementally, one step at a time, e.g. to
+/// fit within the weight budget of each block.
+///
+/// Same as [`reduce`], it is strictly assumed that the `who` attribute of all assignments in
+/// `state` are unique.
+pub fn verify_reduce_step<A: IdentifierT>(
+	mut state: Vec<StakedAssignment<A>>,
+	step: &ReduceStep<A>,
+) -> Result<Vec<StakedAssignment<A>>, VerifyError> {
+	// the (decreased, increased) stake of each voter and target.
+	let mut voter_flow = BTreeMap::<A, (ExtendedBalance, ExtendedBalance)>::new();
+	let mut target_flow = BTreeMap::<A, (ExtendedBalance, ExtendedBalance)>::new();
+	for (voter, target, stake) in step.decrease.iter() {
+		let flow = voter_flow.entry(voter.clone()).or_default();
+		flow.0 = flow.0.saturating_add(*stake);
+		let flow = target_flow.entry(target.clone()).or_default();
+		flow.0 = flow.0.saturating_add(*stake);
+	}
+	for (voter, target, stake) in step.increase.iter() {
+		let flow = voter_flow.entry(voter.clone()).or_default();
+		flow.1 = flow.1.saturating_add(*stake);
+		let flow = target_flow.entry(target.clone()).or_default();
+		flow.1 = flow.1.saturating_add(*stake);
+	}
+	if voter_flow.values().any(|(decreased, increased)| decreased != increased) {
+		return Err(VerifyError::BudgetChanged);
+	}
+	if target_flow.values().any(|(decreased, increased)| decreased != increased) {
+		return Err(VerifyError::SupportChanged);
+	}
+
+	for (voter, target, stake) in step.decrease.iter() {
+		let edge = edge_stake_mut(&mut state, voter, target)?;
+		*edge = edge.checked_sub(*stake).ok_or(VerifyError::EdgeUnderflow)?;
+	}
+	for (voter, target, stake) in step.increase.iter() {
+		let edge = edge_stake_mut(&mut state, voter, target)?;
+		*edge = edge.saturating_add(*stake);
+	}
+	state.iter_mut().for_each(|a| a.distribution.retain(|(_, stake)| !stake.is_zero()));
+
+	Ok(state)
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -772,7 +1152,7 @@ mod tests {
 			},
 		];
 
-		assert_eq!(3, reduce_all(&mut assignments));
+		assert_eq!(3, reduce_all(&mut assignments, |_, _| 0));
 
 		assert_eq!(
 			assignments,
@@ -1072,7 +1452,223 @@ mod tests {
 
 		let n = 4;
 		let m = winners.len() as u32;
-		let num_reduced = reduce_all(&mut assignments);
+		let num_reduced = reduce_all(&mut assignments, |_, _| 0);
 		assert!(16 - num_reduced <= n + m);
 	}
+
+	#[test]
+	fn reduce_prioritized_removes_low_priority_edge() {
+		let assignments = vec![
+			StakedAssignment { who: 1, distribution: vec![(10, 2), (20, 8)] },
+			StakedAssignment { who: 2, distribution: vec![(10, 7), (20, 3)] },
+		];
+
+		// without priorities, the minimum edge (1, 10) is removed.
+		let mut plain = assignments.clone();
+		assert_eq!(reduce(&mut plain), 1);
+		assert_eq!(
+			plain,
+			vec![
+				StakedAssignment { who: 1, distribution: vec![(20, 10)] },
+				StakedAssignment { who: 2, distribution: vec![(10, 9), (20, 1)] },
+			],
+		);
+
+		// (1, 10) is sticky, so (2, 10) is removed instead.
+		let mut prioritized = assignments.clone();
+		let priority_of = |v: &u32, t: &u32| if (*v, *t) == (1, 10) { 1 } else { 0 };
+		assert_eq!(reduce_prioritized(&mut prioritized, priority_of), 1);
+		assert_eq!(
+			prioritized,
+			vec![
+				StakedAssignment { who: 1, distribution: vec![(10, 9), (20, 1)] },
+				StakedAssignment { who: 2, distribution: vec![(20, 10)] },
+			],
+		);
+	}
+
+	#[test]
+	fn reduce_achieves_min_edges_bound() {
+		let mut assignments = vec![
+			StakedAssignment { who: 1, distribution: vec![(10, 10)] },
+			StakedAssignment { who: 2, distribution: vec![(10, 15), (20, 5)] },
+			StakedAssignment { who: 3, distribution: vec![(20, 15), (40, 15)] },
+			StakedAssignment { who: 4, distribution: vec![(20, 10), (30, 10), (40, 20)] },
+			StakedAssignment { who: 5, distribution: vec![(20, 20), (30, 10), (40, 20)] },
+		];
+		let count_edges = |assignments: &[StakedAssignment<u32>]| {
+			assignments.iter().map(|a| a.distribution.len()).sum::<usize>()
+		};
+
+		// 5 voters and 4 targets, all in the same component.
+		assert_eq!(min_edges_bound(&assignments), 8);
+		assert_eq!(count_edges(&assignments), 11);
+
+		reduce(&mut assignments);
+		assert_eq!(count_edges(&assignments), min_edges_bound(&assignments));
+		assert_eq!(count_edges(&assignments), 8);
+
+		// two disjoint components, one of which is a self vote.
+		let assignments = vec![
+			StakedAssignment { who: 1, distribution: vec![(1, 10), (2, 10)] },
+			StakedAssignment { who: 3, distribution: vec![(3, 10)] },
+		];
+		assert_eq!(min_edges_bound(&assignments), 3);
+	}
+
+	#[test]
+	fn reduce_witness_can_be_verified_incrementally() {
+		let initial = vec![
+			StakedAssignment { who: 1, distribution: vec![(10, 2), (20, 8)] },
+			StakedAssignment { who: 2, distribution: vec![(10, 7), (20, 3)] },
+			StakedAssignment { who: 3, distribution: vec![(30, 5), (40, 5)] },
+			StakedAssignment { who: 4, distribution: vec![(30, 4), (40, 6)] },
+		];
+		let mut reduced = initial.clone();
+		assert_eq!(reduce(&mut reduced), 2);
+
+		// one step per cycle.
+		let witness = [
+			ReduceStep {
+				decrease: vec![(1, 10, 2), (2, 20, 2)],
+				increase: vec![(1, 20, 2), (2, 10, 2)],
+			},
+			ReduceStep {
+				decrease: vec![(4, 30, 4), (3, 40, 4)],
+				increase: vec![(3, 30, 4), (4, 40, 4)],
+			},
+		];
+		let mut state = initial.clone();
+		for step in witness.iter() {
+			state = verify_reduce_step(state, step).unwrap();
+		}
+		assert_eq!(state, reduced);
+
+		// a step that changes the support of 10 and 20.
+		let step = ReduceStep { decrease: vec![(1, 10, 2)], increase: vec![(1, 20, 2)] };
+		assert_eq!(verify_reduce_step(initial.clone(), &step), Err(VerifyError::SupportChanged));
+
+		// a step that moves more stake than the edge has.
+		let step = ReduceStep {
+			decrease: vec![(1, 10, 3), (2, 20, 3)],
+			increase: vec![(1, 20, 3), (2, 10, 3)],
+		};
+		assert_eq!(verify_reduce_step(initial.clone(), &step), Err(VerifyError::EdgeUnderflow));
+
+		// a step that creates a new edge.
+		let step = ReduceStep {
+			decrease: vec![(1, 10, 2), (3, 30, 2)],
+			increase: vec![(1, 30, 2), (3, 10, 2)],
+		};
+		assert_eq!(verify_reduce_step(initial, &step), Err(VerifyError::UnknownEdge));
+	}
+
+	#[test]
+	#[cfg(feature = "parallel")]
+	fn reduce_parallel_is_same_as_reduce() {
+		let assignments = vec![
+			StakedAssignment { who: 1, distribution: vec![(10, 10)] },
+			StakedAssignment { who: 2, distribution: vec![(10, 15), (20, 5)] },
+			StakedAssignment { who: 3, distribution: vec![(20, 15), (40, 15)] },
+			StakedAssignment { who: 6, distribution: vec![(50, 2), (60, 8)] },
+			StakedAssignment { who: 4, distribution: vec![(20, 10), (30, 10), (40, 20)] },
+			StakedAssignment { who: 5, distribution: vec![(20, 20), (30, 10), (40, 20)] },
+			StakedAssignment { who: 7, distribution: vec![(50, 7), (60, 3)] },
+			StakedAssignment { who: 8, distribution: vec![(70, 5)] },
+		];
+		assert_eq!(
+			connected_components(&assignments),
+			vec![vec![0, 1, 2, 4, 5], vec![3, 6], vec![7]],
+		);
+
+		let mut sequential = assignments.clone();
+		let mut parallel = assignments;
+		assert_eq!(reduce_parallel(&mut parallel, 2), reduce(&mut sequential));
+		assert_eq!(parallel, sequential);
+		assert_eq!(reduce_parallel(&mut parallel, 2), 0);
+	}
+
+	#[test]
+	#[cfg(feature = "parallel")]
+	fn reduce_parallel_works_with_more_components_than_workers() {
+		// every pair of voters forms a separate cycle of length 4.
+		let assignments = (0..10u64)
+			.flat_map(|i| {
+				let (who, target) = (i * 2, 1000 + i * 2);
+				vec![
+					StakedAssignment { who, distribution: vec![(target, 5), (target + 1, 5)] },
+					StakedAssignment {
+						who: who + 1,
+						distribution: vec![(target, 3), (target + 1, 7)],
+					},
+				]
+			})
+			.collect::<Vec<_>>();
+
+		let mut sequential = assignments.clone();
+		assert_eq!(reduce(&mut sequential), 10);
+		for workers in [0, 1, 3, 16].iter() {
+			let mut parallel = assignments.clone();
+			assert_eq!(reduce_parallel(&mut parallel, *workers), 10);
+			assert_eq!(parallel, sequential);
+		}
+	}
+
+	#[test]
+	fn reduce_with_forest_returns_spanning_forest() {
+		let mut assignments = vec![
+			StakedAssignment { who: 1, distribution: vec![(10, 10)] },
+			StakedAssignment { who: 2, distribution: vec![(10, 15), (20, 5)] },
+			StakedAssignment { who: 3, distribution: vec![(20, 15), (40, 15)] },
+			StakedAssignment { who: 4, distribution: vec![(20, 10), (30, 10), (40, 20)] },
+			StakedAssignment { who: 5, distribution: vec![(20, 20), (30, 10), (40, 20)] },
+			StakedAssignment { who: 6, distribution: vec![(50, 2), (60, 8)] },
+			StakedAssignment { who: 7, distribution: vec![(50, 7), (60, 3)] },
+		];
+		let mut expected = assignments.clone();
+		let expected_removed = reduce(&mut expected);
+
+		let (removed, forest) = reduce_with_forest(&mut assignments).unwrap();
+		assert_eq!(removed, expected_removed);
+		assert_eq!(assignments, expected);
+
+		let voter = |who| NodeId::from(who, NodeRole::Voter);
+		let target = |who| NodeId::from(who, NodeRole::Target);
+		let edges = assignments
+			.iter()
+			.flat_map(|a| a.distribution.iter().map(move |(t, _)| (voter(a.who), target(*t))))
+			.collect::<Vec<_>>();
+		let mut nodes = edges
+			.iter()
+			.flat_map(|(v, t)| vec![v.clone(), t.clone()])
+			.collect::<Vec<_>>();
+		nodes.sort();
+		nodes.dedup();
+
+		// union-find over the nodes: adding each edge of the forest must merge two trees.
+		let index_of = |id: &NodeId<u32>| nodes.binary_search(id).unwrap();
+		let mut parents = (0..nodes.len()).collect::<Vec<_>>();
+		fn find(parents: &[usize], mut x: usize) -> usize {
+			while parents[x] != x {
+				x = parents[x];
+			}
+			x
+		}
+		for (child, parent) in forest.iter() {
+			// every edge of the forest is an edge of the reduced assignments.
+			assert!(edges
+				.iter()
+				.any(|(v, t)| (v, t) == (child, parent) || (t, v) == (child, parent)));
+			let (x, y) = (find(&parents, index_of(child)), find(&parents, index_of(parent)));
+			assert_ne!(x, y, "cycle in the forest");
+			parents[x] = y;
+		}
+
+		// the forest spans all the nodes: both ends of every edge are in the same tree.
+		for (v, t) in edges.iter() {
+			assert_eq!(find(&parents, index_of(v)), find(&parents, index_of(t)));
+		}
+		// two components, each of which is a tree over all its nodes.
+		assert_eq!(forest.len(), nodes.len() - 2);
+	}
 }
@@ -0,0 +1,223 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Proportional allocation of seats among the targets of a support map.
+//!
+//! This is useful for parliament-style bodies, where each elected target (e.g. a party) gets a
+//! number of seats proportional to its support, rather than a single one.
+
+use crate::{IdentifierT, ExtendedBalance, SupportMap, U256, internal};
+use sp_arithmetic::helpers_128bit::multiply_by_rational;
+use sp_std::{prelude::*, collections::btree_map::BTreeMap};
+
+/// Allocate `total_seats` among the targets of `support_map`, proportional to their total
+/// support, using the largest remainder (Hamilton) method.
+///
+/// Each target first gets the integer part of its quota, `total_seats * support / total_support`.
+/// The remaining seats are then given, one each, to the targets with the largest remainders. Ties
+/// are broken in favour of the smaller id. The seats of all targets sum up to exactly
+/// `total_seats`, unless the total support is zero, in which case no seat is allocated.
+pub fn allocate_seats<AccountId: IdentifierT>(
+	support_map: &SupportMap<AccountId>,
+	total_seats: u32,
+) -> BTreeMap<AccountId, u32> {
+	let total_support = support_map
+		.values()
+		.fold(0 as ExtendedBalance, |acc, s| acc.saturating_add(s.total));
+	let seats = ExtendedBalance::from(total_seats);
+	if total_support == 0 {
+		return support_map.keys().map(|who| (who.clone(), 0)).collect()
+	}
+
+	// (who, seats, remainder), in ascending order of id.
+	let mut quotas = support_map
+		.iter()
+		.map(|(who, support)| {
+			// `support <= total_support`, thus this fits in a `u32` and cannot fail.
+			let quota = multiply_by_rational(support.total, seats, total_support).unwrap_or(0);
+			let remainder = U256::full_mul(support.total, seats)
+				.saturating_sub(U256::full_mul(quota, total_support));
+			(who.clone(), quota as u32, remainder)
+		})
+		.collect::<Vec<_>>();
+
+	let allocated = quotas.iter().map(|(_, s, _)| *s).sum::<u32>();
+	let mut by_remainder = quotas
+		.iter()
+		.map(|(who, _, remainder)| (who.clone(), *remainder))
+		.enumerate()
+		.collect::<Vec<_>>();
+	internal::stable_sort_by_key_then_id(
+		&mut by_remainder,
+		|(_, (_, remainder))| Reverse(*remainder),
+		|(_, (who, _))| who,
+	);
+	let take = total_seats.saturating_sub(allocated) as usize;
+	for (index, _) in by_remainder.into_iter().take(take) {
+		quotas[index].1 += 1;
+	}
+
+	quotas.into_iter().map(|(who, seats, _)| (who, seats)).collect()
+}
+
+/// A highest averages method of seat allocation, see [`allocate_seats_divisor`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum DivisorMethod {
+	/// The D'Hondt (Jefferson) method, with divisors `1, 2, 3, ...`. This slightly favours the
+	/// targets with larger support.
+	DHondt,
+	/// The Sainte-Laguë (Webster) method, with divisors `1, 3, 5, ...`. This is more favourable to
+	/// the targets with smaller support than [`DivisorMethod::DHondt`].
+	SainteLague,
+}
+
+impl DivisorMethod {
+	/// The divisor of a target that already has `seats` seats.
+	fn divisor(&self, seats: u32) -> ExtendedBalance {
+		let seats = ExtendedBalance::from(seats);
+		match self {
+			DivisorMethod::DHondt => seats + 1,
+			DivisorMethod::SainteLague => 2 * seats + 1,
+		}
+	}
+}
+
+/// Allocate `total_seats` among the targets of `support_map`, proportional to their total
+/// support, using the given highest averages `method`.
+///
+/// Seats are allocated one by one, each to the target with the highest `support / divisor`, where
+/// the divisor depends on the number of seats that the target already has. Ties are broken in
+/// favour of the smaller id. The seats of all targets sum up to exactly `total_seats`, unless the
+/// total support is zero, in which case no seat is allocated.
+///
+/// This is `O(total_seats ⋅ targets)`.
+pub fn allocate_seats_divisor<AccountId: IdentifierT>(
+	support_map: &SupportMap<AccountId>,
+	total_seats: u32,
+	method: DivisorMethod,
+) -> BTreeMap<AccountId, u32> {
+	// (who, support, seats), in ascending order of id.
+	let mut allocation = support_map
+		.iter()
+		.map(|(who, support)| (who.clone(), support.total, 0u32))
+		.collect::<Vec<_>>();
+	if allocation.iter().all(|(_, support, _)| *support == 0) {
+		return allocation.into_iter().map(|(who, _, _)| (who, 0)).collect()
+	}
+
+	for _ in 0..total_seats {
+		let mut best = 0;
+		for index in 1..allocation.len() {
+			let (_, support, seats) = &allocation[index];
+			let (_, best_support, best_seats) = &allocation[best];
+			// support / divisor > best_support / best_divisor, without any loss of precision.
+			if U256::full_mul(*support, method.divisor(*best_seats)) >
+				U256::full_mul(*best_support, method.divisor(*seats))
+			{
+				best = index;
+			}
+		}
+		allocation[best].2 += 1;
+	}
+
+	allocation.into_iter().map(|(who, _, seats)| (who, seats)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Support;
+
+	fn support_map_of(totals: &[(u32, ExtendedBalance)]) -> SupportMap<u32> {
+		totals
+			.iter()
+			.map(|(who, total)| (*who, Support { total: *total, voters: vec![] }))
+			.collect()
+	}
+
+	#[test]
+	fn hamilton_allocation_works() {
+		let support_map = support_map_of(&[(1, 50), (2, 30), (3, 20)]);
+		assert_eq!(
+			allocate_seats(&support_map, 10),
+			vec![(1, 5), (2, 3), (3, 2)].into_iter().collect(),
+		);
+
+		// quotas are 3.5, 2.1 and 1.4, the only remaining seat goes to the largest remainder.
+		assert_eq!(
+			allocate_seats(&support_map, 7),
+			vec![(1, 4), (2, 2), (3, 1)].into_iter().collect(),
+		);
+
+		// quotas are 1/3 each, ties are broken by id.
+		let support_map = support_map_of(&[(1, 10), (2, 10), (3, 10)]);
+		assert_eq!(
+			allocate_seats(&support_map, 2),
+			vec![(1, 1), (2, 1), (3, 0)].into_iter().collect(),
+		);
+
+		// no support, no seats.
+		let support_map = support_map_of(&[(1, 0), (2, 0)]);
+		assert_eq!(
+			allocate_seats(&support_map, 2),
+			vec![(1, 0), (2, 0)].into_iter().collect(),
+		);
+	}
+
+	#[test]
+	fn hamilton_allocation_does_not_overflow() {
+		let quarter = ExtendedBalance::MAX / 4;
+		let support_map = support_map_of(&[(1, 2 * quarter), (2, quarter), (3, quarter)]);
+		let seats = allocate_seats(&support_map, 101);
+		assert_eq!(seats.values().sum::<u32>(), 101);
+		assert_eq!(seats, vec![(1, 51), (2, 25), (3, 25)].into_iter().collect());
+	}
+
+	#[test]
+	fn divisor_allocation_works() {
+		let support_map = support_map_of(&[(1, 100), (2, 80), (3, 30)]);
+		let dhondt = |seats| allocate_seats_divisor(&support_map, seats, DivisorMethod::DHondt);
+		let sainte_lague =
+			|seats| allocate_seats_divisor(&support_map, seats, DivisorMethod::SainteLague);
+
+		// for 10 seats, both methods agree.
+		assert_eq!(dhondt(10), vec![(1, 5), (2, 4), (3, 1)].into_iter().collect());
+		assert_eq!(sainte_lague(10), vec![(1, 5), (2, 4), (3, 1)].into_iter().collect());
+
+		// for 5 seats, 3 only gets a seat with Sainte-Laguë, where the averages of the others
+		// drop faster: 100 / 3 and 80 / 3, rather than 100 / 2 and 80 / 2.
+		assert_eq!(dhondt(5), vec![(1, 3), (2, 2), (3, 0)].into_iter().collect());
+		assert_eq!(sainte_lague(5), vec![(1, 2), (2, 2), (3, 1)].into_iter().collect());
+
+		for seats in 0..20 {
+			assert_eq!(dhondt(seats).values().sum::<u32>(), seats);
+			assert_eq!(sainte_lague(seats).values().sum::<u32>(), seats);
+		}
+
+		// ties are broken by id, and no support means no seats.
+		let support_map = support_map_of(&[(1, 10), (2, 10), (3, 10)]);
+		assert_eq!(
+			allocate_seats_divisor(&support_map, 2, DivisorMethod::DHondt),
+			vec![(1, 1), (2, 1), (3, 0)].into_iter().collect(),
+		);
+		let support_map = support_map_of(&[(1, 0), (2, 0)]);
+		assert_eq!(
+			allocate_seats_divisor(&support_map, 2, DivisorMethod::SainteLague),
+			vec![(1, 0), (2, 0)].into_iter().collect(),
+		);
+	}
+}
@@ -21,10 +21,22 @@ use crate::mock::*;
 use crate::{
 	seq_phragmen, balancing, build_support_map, is_score_better, helpers::*,
 	Support, StakedAssignment, Assignment, ElectionResult, ExtendedBalance, setup_inputs,
-	seq_phragmen_core, Voter,
+	seq_phragmen_core, Voter, entry_threshold, seq_phragmen_with_dust_merging, start_election, step,
+	finalize, ElectionScore, ScoreReport, VoteWeight, edge_slack, reduce, evaluate_support,
+	WinnerExplanation, IncrementalElection, evaluate_support_exact, is_exact_score_better, U256,
+	SupportMap, apply_slash, min_voter_flip_to_change_winners, summarize, nakamoto_coefficient,
+	ElectionSummary, seq_phragmen_softcap, cross_check, CrossCheckReport,
+	evaluate_support_streaming, seat_prices, SupportMapExt, elect_quadratic, approval_ranking,
+	seq_phragmen_with_min_backers, seq_phragmen_with_min_utilization, CompressedResult,
+	CompressedAssignment, Error, LoadModel, StandardLoad, seq_phragmen_with_load_model,
+	stake_to_unseat, estimate_election_weight, estimate_election_weight_with,
+	ElectionWeightCoefficients, seq_phragmen_presorted, AssignmentError, seq_phragmen_seeded,
+	replay_election, min_support_of_winners, Exposure, IndividualExposure, seq_phragmen_bounded,
+	commission_adjusted_score, elect_with_and_without_balancing, kingmaker_voters,
 };
 use substrate_test_utils::assert_eq_uvec;
-use sp_arithmetic::{Perbill, Permill, Percent, PerU16};
+use sp_arithmetic::{Perbill, Permill, Percent, PerU16, Rational128};
+use std::collections::BTreeMap;
 
 #[test]
 fn float_phragmen_poc_works() {
@@ -117,6 +129,36 @@ fn phragmen_core_poc_works() {
 	);
 }
 
+#[test]
+fn phragmen_in_steps_is_same_as_single_shot() {
+	let candidates = vec![1, 2, 3, 4, 5];
+	let voters = vec![
+		(10, 10, vec![1, 2]),
+		(20, 20, vec![1, 3]),
+		(30, 30, vec![1, 2, 3, 4]),
+		(40, 40, vec![1, 3, 4, 5]),
+		(50, 50, vec![2, 4, 5]),
+	];
+
+	let mut state = start_election(4, candidates.clone(), voters.clone(), Some((4, 0)));
+	let mut steps = 0;
+	while !step(&mut state, 1) {
+		steps += 1;
+		assert_eq!(state.rounds_executed(), steps);
+	}
+	assert!(state.is_done());
+	assert_eq!(state.rounds_executed(), 4);
+	// once done, stepping is a noop.
+	assert!(step(&mut state, 1));
+	assert_eq!(state.rounds_executed(), 4);
+
+	let stepped = finalize::<_, Perbill>(state).unwrap();
+	let single_shot = seq_phragmen::<_, Perbill>(4, candidates, voters, Some((4, 0))).unwrap();
+
+	assert_eq!(stepped.winners, single_shot.winners);
+	assert_eq!(stepped.assignments, single_shot.assignments);
+}
+
 #[test]
 fn balancing_core_works() {
 	let candidates = vec![1, 2, 3, 4, 5];
@@ -173,6 +215,82 @@ fn balancing_core_works() {
 	);
 }
 
+#[test]
+fn balancing_shuffled_is_independent_of_voter_order() {
+	use codec::Encode;
+
+	let candidates = vec![1, 2, 3, 4, 5];
+	let voters = vec![
+		(10, 10, vec![1, 2]),
+		(20, 20, vec![1, 3]),
+		(30, 30, vec![1, 2, 3, 4]),
+		(40, 40, vec![1, 3, 4, 5]),
+		(50, 50, vec![2, 4, 5]),
+	];
+	let balanced = |voters: Vec<(AccountId, VoteWeight, Vec<AccountId>)>, seed| {
+		let (candidates, voters) = setup_inputs(candidates.clone(), voters);
+		let (_, mut voters) = seq_phragmen_core(4, candidates, voters).unwrap();
+		balancing::balance_shuffled::<AccountId>(&mut voters, 2, 0, seed);
+		let mut outcome = voters
+			.iter()
+			.map(|v| (v.who, v.edges.iter().map(|e| (e.who, e.weight)).collect::<Vec<_>>()))
+			.collect::<Vec<_>>();
+		outcome.sort();
+		outcome.encode()
+	};
+
+	let mut reversed = voters.clone();
+	reversed.reverse();
+	assert_eq!(balanced(voters.clone(), 7), balanced(reversed, 7));
+
+	let mut rotated = voters.clone();
+	rotated.rotate_left(2);
+	assert_eq!(balanced(voters, 7), balanced(rotated, 7));
+}
+
+#[test]
+fn seeded_election_can_be_replayed() {
+	let candidates = vec![1, 2, 3, 4, 5];
+	let voters = vec![
+		(10, 10, vec![1, 2]),
+		(20, 20, vec![1, 3]),
+		(30, 30, vec![1, 2, 3, 4]),
+		(40, 40, vec![1, 3, 4, 5]),
+		(50, 50, vec![2, 4, 5]),
+	];
+	let balance = Some((4, 0));
+
+	let mut stream = Vec::new();
+	let recorded = seq_phragmen_seeded::<_, Perbill>(
+		4,
+		candidates.clone(),
+		voters.clone(),
+		balance,
+		42,
+		Some(&mut stream),
+	).unwrap();
+	// each round of balancing shuffles the 5 voters with 4 draws.
+	assert!(!stream.is_empty());
+	assert_eq!(stream.len() % 4, 0);
+
+	let seeded =
+		seq_phragmen_seeded::<_, Perbill>(4, candidates.clone(), voters.clone(), balance, 42, None)
+			.unwrap();
+	let replayed =
+		replay_election::<_, Perbill>(4, candidates.clone(), voters.clone(), balance, &stream)
+			.unwrap();
+	assert_eq!(replayed.winners, recorded.winners);
+	assert_eq!(replayed.assignments, recorded.assignments);
+	assert_eq!(seeded.winners, recorded.winners);
+	assert_eq!(seeded.assignments, recorded.assignments);
+
+	// a truncated stream cannot be replayed.
+	assert_eq!(
+		replay_election::<_, Perbill>(4, candidates, voters, balance, &stream[1..]).unwrap_err(),
+		"random stream exhausted",
+	);
+}
+
 #[test]
 fn voter_normalize_ops_works() {
 	use crate::{Candidate, Edge};
@@ -293,6 +411,75 @@ fn phragmen_poc_works() {
 	);
 }
 
+#[test]
+fn entry_threshold_is_backing_of_last_winner() {
+	let candidates = vec![1, 2, 3];
+	let voters = vec![
+		(10, 10, vec![1, 2]),
+		(20, 20, vec![1, 3]),
+		(30, 30, vec![2, 3]),
+	];
+
+	let ElectionResult { winners, assignments: _ } = seq_phragmen::<_, Perbill>(
+		2,
+		candidates.clone(),
+		voters.clone(),
+		None,
+	).unwrap();
+	assert_eq!(winners, vec![(3, 35), (2, 25)]);
+
+	assert_eq!(entry_threshold(candidates.clone(), voters.clone(), 2).unwrap(), 25);
+	assert_eq!(entry_threshold(candidates, voters, 0).unwrap(), 0);
+}
+
+#[test]
+fn dust_voters_are_merged_and_expanded() {
+	let candidates = vec![1, 2, 3];
+	let voters = vec![
+		(10, 10, vec![1, 2]),
+		(20, 20, vec![1, 3]),
+		(30, 30, vec![2, 3]),
+		(40, 2, vec![1, 2]),
+		(50, 2, vec![2, 1]),
+		(60, 2, vec![1, 2, 2]),
+	];
+
+	let (merged, members) = merge_dust_voters(voters.clone(), 5);
+	assert_eq!(
+		merged,
+		vec![
+			(10, 10, vec![1, 2]),
+			(20, 20, vec![1, 3]),
+			(30, 30, vec![2, 3]),
+			(40, 6, vec![1, 2]),
+		],
+	);
+	assert_eq!(members.get(&40).unwrap(), &vec![50, 60]);
+
+	let ElectionResult { winners, assignments } = seq_phragmen_with_dust_merging::<_, Perbill>(
+		2,
+		candidates.clone(),
+		voters.clone(),
+		None,
+		5,
+	).unwrap();
+	let unmerged = seq_phragmen::<_, Perbill>(2, candidates, voters, None).unwrap();
+
+	assert_eq!(winners, unmerged.winners);
+	assert_eq!(assignments.len(), 6);
+	let distribution_of = |who| assignments.iter().find(|a| a.who == who).unwrap().distribution.clone();
+	assert_eq!(distribution_of(50), distribution_of(40));
+	assert_eq!(distribution_of(60), distribution_of(40));
+	assert_eq!(distribution_of(40), unmerged.assignments.iter().find(|a| a.who == 40).unwrap().distribution);
+
+	let stake_of = create_stake_of(&[(10, 10), (20, 20), (30, 30), (40, 2), (50, 2), (60, 2)]);
+	let staked = assignment_ratio_to_staked(assignments, &stake_of);
+	// each dust voter gets its own share of the synthetic voter's assignment.
+	for who in &[40u64, 50, 60] {
+		assert_eq!(staked.iter().find(|a| a.who == *who).unwrap().total(), 2);
+	}
+}
+
 #[test]
 fn phragmen_poc_works_with_balancing() {
 	let candidates = vec![1, 2, 3];
@@ -687,6 +874,463 @@ fn elect_has_no_entry_barrier() {
 	]);
 }
 
+#[test]
+fn phragmen_softcap_flattens_support() {
+	let candidates = vec![1, 2];
+	let voters = vec![(10, 100, vec![1, 2]), (20, 100, vec![1]), (30, 30, vec![2])];
+	let stake_of = |who: &AccountId| voters.iter().find(|(v, _, _)| v == who).unwrap().1;
+	let supports_of = |result: ElectionResult<AccountId, Perbill>| {
+		let winners = to_without_backing(result.winners);
+		let staked = assignment_ratio_to_staked(result.assignments, stake_of);
+		build_support_map(&winners, &staked).unwrap()
+	};
+
+	let plain = seq_phragmen::<_, Perbill>(2, candidates.clone(), voters.clone(), None).unwrap();
+	let plain = supports_of(plain);
+
+	// no penalty is the same as no soft cap.
+	let no_penalty = seq_phragmen_softcap::<_, Perbill>(
+		2,
+		candidates.clone(),
+		voters.clone(),
+		150,
+		Perbill::zero(),
+	).unwrap();
+	assert_eq!(supports_of(no_penalty), plain);
+
+	let capped = seq_phragmen_softcap::<_, Perbill>(
+		2,
+		candidates,
+		voters.clone(),
+		150,
+		Perbill::from_percent(50),
+	).unwrap();
+	let capped = supports_of(capped);
+
+	// no one is excluded.
+	assert_eq!(capped.keys().collect::<Vec<_>>(), vec![&1, &2]);
+	// 10 moved some of its stake from 1 to 2.
+	assert!(capped.get(&1).unwrap().total < plain.get(&1).unwrap().total);
+	assert!(capped.get(&2).unwrap().total > plain.get(&2).unwrap().total);
+	assert!(evaluate_support(&capped)[0] > evaluate_support(&plain)[0]);
+	assert!(evaluate_support(&capped)[2] < evaluate_support(&plain)[2]);
+}
+
+#[test]
+fn seat_prices_are_non_decreasing() {
+	let candidates = vec![1, 2, 3, 4, 5];
+	let voters = (0..20u64)
+		.map(|i| (100 + i, 1_000, vec![i % 5 + 1, (i + 1) % 5 + 1, (i + 3) % 5 + 1]))
+		.collect::<Vec<_>>();
+
+	let prices = seat_prices(candidates.clone(), voters.clone(), 4).unwrap();
+	let result = seq_phragmen::<_, Perbill>(4, candidates, voters, None).unwrap();
+	assert_eq!(
+		prices.iter().map(|(who, _)| *who).collect::<Vec<_>>(),
+		to_without_backing(result.winners),
+	);
+
+	// everyone has the same approval stake, the first seat costs exactly 1 / 12_000.
+	assert_eq!(prices[0].1, ExtendedBalance::MAX / 12_000);
+	assert!(prices.windows(2).all(|w| w[0].1 <= w[1].1));
+	assert!(prices[0].1 < prices[3].1);
+}
+
+#[test]
+fn approval_ranking_can_differ_from_winners() {
+	let candidates = vec![10, 20, 30, 40];
+	let voters = vec![
+		(1, 100, vec![10, 20]),
+		(2, 100, vec![10, 20]),
+		(3, 120, vec![30]),
+		(4, 50, vec![20, 40]),
+	];
+
+	// 40 has no approval other than that of 4, and is ranked last.
+	assert_eq!(
+		approval_ranking(candidates.clone(), voters.clone()),
+		vec![(20, 250), (10, 200), (30, 120), (40, 50)],
+	);
+
+	// 10 and 20 share most of their voters, hence 30 is elected before 10.
+	let result = seq_phragmen::<_, Perbill>(2, candidates, voters, None).unwrap();
+	assert_eq!(to_without_backing(result.winners), vec![20, 30]);
+}
+
+#[test]
+fn phragmen_min_backers_demotes_single_backer_winner() {
+	let candidates = vec![10, 20, 30];
+	let voters = vec![
+		(1, 1_000, vec![10]),
+		(2, 100, vec![20, 30]),
+		(3, 100, vec![20]),
+		(4, 50, vec![30]),
+	];
+
+	let result = seq_phragmen::<_, Perbill>(2, candidates.clone(), voters.clone(), None).unwrap();
+	assert_eq!(to_without_backing(result.winners), vec![10, 20]);
+
+	// same as seq-phragmen without the constraint.
+	let result = seq_phragmen_with_min_backers::<_, Perbill>(
+		2,
+		candidates.clone(),
+		voters.clone(),
+		None,
+		1,
+	).unwrap();
+	assert_eq!(to_without_backing(result.winners), vec![10, 20]);
+
+	// 10 is only backed by 1, and its seat is filled by 30.
+	let result = seq_phragmen_with_min_backers::<_, Perbill>(2, candidates, voters, None, 2)
+		.unwrap();
+	assert_eq!(to_without_backing(result.winners), vec![20, 30]);
+	assert!(result.assignments.iter().all(|a| a.distribution.iter().all(|(t, _)| *t != 10)));
+}
+
+#[test]
+fn split_exposure_works() {
+	let support = Support::<AccountId> {
+		total: 100,
+		voters: vec![(2, 30), (1, 50), (3, 20)],
+	};
+	assert_eq!(split_exposure(&support, &1), (50, vec![(2, 30), (3, 20)]));
+
+	// no self vote.
+	assert_eq!(split_exposure(&support, &4), (0, vec![(2, 30), (1, 50), (3, 20)]));
+}
+
+#[test]
+fn exposure_change_works() {
+	let support = |voters: Vec<(AccountId, ExtendedBalance)>| Support {
+		total: voters.iter().map(|(_, w)| w).sum(),
+		voters,
+	};
+	let mut previous = SupportMap::<AccountId>::new();
+	previous.insert(10, support(vec![(1, 50), (2, 30)]));
+	previous.insert(20, support(vec![(1, 20)]));
+	previous.insert(30, support(vec![(1, 30), (3, 10)]));
+
+	// 20 has left the set, and 40 has joined it.
+	let mut current = SupportMap::<AccountId>::new();
+	current.insert(10, support(vec![(1, 40), (2, 30)]));
+	current.insert(30, support(vec![(1, 30)]));
+	current.insert(40, support(vec![(1, 30), (3, 10)]));
+
+	assert_eq!(exposure_change(&previous, &current, &1), vec![(10, -10), (20, -20), (40, 30)]);
+	assert_eq!(exposure_change(&previous, &current, &2), vec![]);
+	assert_eq!(exposure_change(&previous, &current, &3), vec![(30, -10), (40, 10)]);
+}
+
+#[test]
+fn phragmen_min_utilization_reassigns_wasted_voters() {
+	let candidates = vec![10, 20, 30];
+	let voters = vec![
+		(1, 100, vec![10]),
+		(2, 50, vec![20]),
+		(3, 10, vec![30]),
+		(4, 10, vec![40]),
+	];
+
+	// 3 backs only a loser, and is wasted.
+	let result = seq_phragmen::<_, Perbill>(2, candidates.clone(), voters.clone(), None).unwrap();
+	assert_eq!(result.winners, vec![(10, 100), (20, 50)]);
+	assert!(result.assignments.iter().all(|a| a.who != 3));
+
+	// half of the stake of 3 is moved to the weakest winner. 4 has no valid votes.
+	let (utilized, reallocations) = seq_phragmen_with_min_utilization::<_, Perbill>(
+		2,
+		candidates,
+		voters,
+		None,
+		Perbill::from_percent(50),
+	).unwrap();
+	assert_eq!(reallocations, vec![(3, 20, 5)]);
+
+	// the result itself is that of the plain election, with all of its assignments valid.
+	assert_eq!(utilized.winners, result.winners);
+	assert_eq!(utilized.assignments, result.assignments);
+	let winners = to_without_backing(utilized.winners);
+	assert!(utilized.assignments.iter().all(|a| a.validate(&winners).is_ok()));
+
+	// the weakest winner is picked anew for each wasted voter.
+	let voters = vec![
+		(1, 60, vec![10]),
+		(2, 50, vec![20]),
+		(3, 10, vec![30]),
+		(4, 44, vec![40]),
+		(5, 20, vec![30]),
+	];
+	let (result, reallocations) = seq_phragmen_with_min_utilization::<_, Perbill>(
+		2,
+		vec![10, 20, 30, 40],
+		voters,
+		None,
+		Perbill::from_percent(50),
+	).unwrap();
+	assert_eq!(result.winners, vec![(10, 60), (20, 50)]);
+	assert_eq!(reallocations, vec![(3, 20, 5), (4, 20, 22), (5, 10, 10)]);
+}
+
+#[test]
+fn phragmen_with_custom_load_model_works() {
+	// the effective load is the current load, scaled by a constant factor.
+	struct LinearLoad(ExtendedBalance);
+	impl LoadModel for LinearLoad {
+		fn update_load(&self, current: Rational128, _: ExtendedBalance) -> Rational128 {
+			Rational128::from(current.n().saturating_mul(self.0), current.d())
+		}
+	}
+
+	let candidates = vec![10, 20, 30, 40];
+	let voters = vec![
+		(1, 100, vec![10, 20]),
+		(2, 100, vec![10, 20]),
+		(3, 120, vec![30]),
+		(4, 50, vec![20, 40]),
+	];
+
+	// the standard load model yields exactly the same result as seq-phragmen.
+	let standard = seq_phragmen::<_, Perbill>(2, candidates.clone(), voters.clone(), None)
+		.unwrap();
+	let default = seq_phragmen_with_load_model::<_, Perbill, _>(
+		2,
+		candidates.clone(),
+		voters.clone(),
+		&StandardLoad,
+	).unwrap();
+	assert_eq!(to_without_backing(standard.winners.clone()), vec![20, 30]);
+	assert_eq!(default.winners, standard.winners);
+	assert_eq!(default.assignments, standard.assignments);
+
+	// also with rounding involved, on a larger election.
+	let candidates_big = (1..=30).collect::<Vec<AccountId>>();
+	let voters_big = (100..300)
+		.map(|v: AccountId| {
+			let targets = (0..(v % 5 + 1)).map(|i| (v * 7 + i * 11) % 30 + 1).collect::<Vec<_>>();
+			(v, v * 7919 % 1000 + 1, targets)
+		})
+		.collect::<Vec<_>>();
+	let standard = seq_phragmen::<_, Perbill>(10, candidates_big.clone(), voters_big.clone(), None)
+		.unwrap();
+	let default = seq_phragmen_with_load_model::<_, Perbill, _>(
+		10,
+		candidates_big,
+		voters_big,
+		&StandardLoad,
+	).unwrap();
+	assert_eq!(default.winners, standard.winners);
+	assert_eq!(default.assignments, standard.assignments);
+
+	// a linear scaling of all loads does not change the outcome.
+	let scaled = seq_phragmen_with_load_model::<_, Perbill, _>(
+		2,
+		candidates.clone(),
+		voters.clone(),
+		&LinearLoad(3),
+	).unwrap();
+	assert_eq!(to_without_backing(scaled.winners), vec![20, 30]);
+
+	// without any load, this is plain approval voting.
+	let result = seq_phragmen_with_load_model::<_, Perbill, _>(
+		2,
+		candidates,
+		voters,
+		&LinearLoad(0),
+	).unwrap();
+	assert_eq!(to_without_backing(result.winners.clone()), vec![20, 10]);
+	assert!(result.assignments.iter().all(|a| a.distribution.iter().all(|(t, _)| *t != 30)));
+}
+
+#[test]
+fn election_weight_estimate_is_monotonic() {
+	let base = (1_000, 100, 5_000, 20);
+	let estimate = |(v, c, e, t)| estimate_election_weight(v, c, e, t);
+
+	assert!(estimate(base) > estimate((0, 0, 0, 0)));
+	assert!(estimate((2_000, 100, 5_000, 20)) > estimate(base));
+	assert!(estimate((1_000, 200, 5_000, 20)) > estimate(base));
+	assert!(estimate((1_000, 100, 10_000, 20)) > estimate(base));
+	assert!(estimate((1_000, 100, 5_000, 40)) > estimate(base));
+
+	// no more rounds than candidates are executed.
+	assert_eq!(estimate((1_000, 100, 5_000, 200)), estimate((1_000, 100, 5_000, 100)));
+	// saturates.
+	assert_eq!(estimate((u32::MAX, u32::MAX, u32::MAX, u32::MAX)), u64::MAX);
+
+	let free = ElectionWeightCoefficients {
+		base: 0,
+		per_voter: 0,
+		per_candidate: 0,
+		per_edge: 1,
+		per_round_edge: 0,
+		per_round_candidate: 0,
+	};
+	assert_eq!(estimate_election_weight_with(&free, 1_000, 100, 5_000, 20), 5_000);
+}
+
+#[test]
+fn phragmen_presorted_is_same_as_phragmen() {
+	let candidates = vec![30, 40, 20, 10, 50];
+	let voters = vec![
+		(1, 100, vec![10, 20]),
+		(2, 100, vec![20, 30]),
+		(3, 150, vec![30, 40]),
+		(4, 80, vec![40, 10]),
+		(5, 20, vec![10, 50]),
+	];
+	// candidates are sorted by approval stake, and 20 and 10 are tied.
+	assert_eq!(
+		approval_ranking(candidates.clone(), voters.clone()),
+		vec![(30, 250), (40, 230), (20, 200), (10, 200), (50, 20)],
+	);
+
+	for balance in [None, Some((10, 0))].iter().cloned() {
+		let expected =
+			seq_phragmen::<_, Perbill>(3, candidates.clone(), voters.clone(), balance).unwrap();
+		let presorted =
+			seq_phragmen_presorted::<_, Perbill>(3, candidates.clone(), voters.clone(), balance)
+				.unwrap();
+		assert_eq!(presorted.winners, expected.winners);
+		assert_eq!(presorted.assignments, expected.assignments);
+	}
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "candidates must be sorted by approval stake")]
+fn phragmen_presorted_rejects_unsorted_candidates_in_debug() {
+	let voters = vec![(1, 100, vec![10]), (2, 200, vec![20])];
+	let _ = seq_phragmen_presorted::<_, Perbill>(1, vec![10, 20], voters, None);
+}
+
+#[test]
+fn assignment_validate_works() {
+	let winners = vec![10, 20, 30];
+	let assignment = |distribution: Vec<(AccountId, Percent)>| Assignment { who: 1, distribution };
+
+	assert_eq!(
+		assignment(vec![(10, Percent::from_percent(40)), (20, Percent::from_percent(60))])
+			.validate(&winners),
+		Ok(()),
+	);
+	// off by one part per target is tolerated.
+	assert_eq!(
+		assignment(vec![
+			(10, Percent::from_percent(33)),
+			(20, Percent::from_percent(33)),
+			(30, Percent::from_percent(33)),
+		]).validate(&winners),
+		Ok(()),
+	);
+
+	assert_eq!(assignment(vec![]).validate(&winners), Err(AssignmentError::EmptyDistribution));
+	assert_eq!(
+		assignment(vec![(10, Percent::from_percent(40)), (40, Percent::from_percent(60))])
+			.validate(&winners),
+		Err(AssignmentError::NonWinnerTarget),
+	);
+	assert_eq!(
+		assignment(vec![(10, Percent::from_percent(40)), (10, Percent::from_percent(60))])
+			.validate(&winners),
+		Err(AssignmentError::DuplicateTarget),
+	);
+	assert_eq!(
+		assignment(vec![(10, Percent::from_percent(40)), (20, Percent::from_percent(50))])
+			.validate(&winners),
+		Err(AssignmentError::InvalidRatioSum),
+	);
+	assert_eq!(
+		assignment(vec![(10, Percent::from_percent(60)), (20, Percent::from_percent(60))])
+			.validate(&winners),
+		Err(AssignmentError::InvalidRatioSum),
+	);
+}
+
+#[test]
+fn seq_phragmen_bounded_caps_the_split_of_each_voter() {
+	// every voter approves every candidate, thus balancing each voter iterates over all winners.
+	// stakes are large, so that the rounding of normalization leaves no visible dust behind.
+	let candidates = (1..=20).collect::<Vec<AccountId>>();
+	let voters = (100..140)
+		.map(|v| (v, (v % 7 + 1) * 1_000_000_000, candidates.clone()))
+		.collect::<Vec<_>>();
+	let balance = Some((50, 0));
+
+	let unbounded = seq_phragmen::<_, Perbill>(10, candidates.clone(), voters.clone(), balance)
+		.unwrap();
+	let bounded = seq_phragmen_bounded::<_, Perbill>(
+		10,
+		candidates.clone(),
+		voters.clone(),
+		balance,
+		100,
+	).unwrap();
+	assert_eq!(bounded.winners, unbounded.winners);
+	assert_eq!(bounded.assignments, unbounded.assignments);
+
+	let ElectionResult { winners, assignments } = seq_phragmen_bounded::<_, Perbill>(
+		10,
+		candidates,
+		voters,
+		balance,
+		2,
+	).unwrap();
+	assert_eq!(winners.len(), 10);
+	assert_eq!(assignments.len(), 40);
+	let winners = winners.into_iter().map(|(w, _)| w).collect::<Vec<_>>();
+	for assignment in assignments {
+		assert!(assignment.distribution.len() <= 2);
+		assert!(assignment.distribution.iter().all(|(t, _)| winners.contains(t)));
+		assert_eq!(
+			assignment.distribution.iter().map(|(_, p)| p.deconstruct()).sum::<u32>(),
+			Perbill::one().deconstruct(),
+		);
+	}
+}
+
+#[test]
+fn quadratic_scaling_reduces_whale_influence() {
+	let candidates = vec![1, 2, 3, 4, 5, 6];
+	// 12 small voters, and a whale with almost 7 times their total stake.
+	let mut voters = (10..22u64).map(|v| (v, 100, vec![1, 2, 3])).collect::<Vec<_>>();
+	voters.push((99, 8_000, vec![4, 5, 6]));
+	let whale_seats = |result: ElectionResult<AccountId, Perbill>| {
+		to_without_backing(result.winners).into_iter().filter(|w| *w >= 4).count()
+	};
+
+	let linear = seq_phragmen::<_, Perbill>(3, candidates.clone(), voters.clone(), None).unwrap();
+	assert_eq!(whale_seats(linear), 3);
+
+	// the whale counts as 51 on each of its targets, each small voter counts as 5 on each of its
+	// targets, hence 60 in total.
+	let quadratic = elect_quadratic::<_, Perbill>(3, candidates, voters).unwrap();
+	assert_eq_uvec!(quadratic.winners.clone(), vec![(1, 60), (2, 60), (3, 60)]);
+	assert_eq!(quadratic.assignments.len(), 12);
+	assert!(quadratic.assignments.iter().all(|a| a.distribution == vec![
+		(1, Perbill::from_parts(333_333_334)),
+		(2, Perbill::from_parts(333_333_333)),
+		(3, Perbill::from_parts(333_333_333)),
+	]));
+	assert_eq!(whale_seats(quadratic), 0);
+}
+
+#[test]
+fn quadratic_scaling_applies_to_each_target() {
+	let candidates = vec![1, 2, 3];
+	// 10 splits 400 among its two valid targets, thus counts as 14 on each. 20 counts as 15 on 3.
+	let voters = vec![(10, 400, vec![1, 2, 4]), (20, 225, vec![3, 3])];
+
+	let result = elect_quadratic::<_, Perbill>(2, candidates, voters).unwrap();
+	assert_eq!(result.winners, vec![(3, 15), (1, 14)]);
+	assert_eq!(
+		result.assignments,
+		vec![
+			Assignment { who: 10, distribution: vec![(1, Perbill::one())] },
+			Assignment { who: 20, distribution: vec![(3, Perbill::one())] },
+		],
+	);
+}
+
 #[test]
 fn phragmen_self_votes_should_be_kept() {
 	let candidates = vec![5, 10, 20, 30];
@@ -731,30 +1375,126 @@ fn phragmen_self_votes_should_be_kept() {
 	assert_eq!(supports.get(&5u64), None);
 	assert_eq!(
 		supports.get(&10u64).unwrap(),
-		&Support { total: 14u128, voters: vec![(10u64, 10u128), (1u64, 4u128)] },
+		&Support { total: 14u128, voters: vec![(1u64, 4u128), (10u64, 10u128)] },
 	);
 	assert_eq!(
 		supports.get(&20u64).unwrap(),
-		&Support { total: 24u128, voters: vec![(20u64, 20u128), (1u64, 4u128)] },
+		&Support { total: 24u128, voters: vec![(1u64, 4u128), (20u64, 20u128)] },
 	);
 }
 
 #[test]
-fn duplicate_target_is_ignored() {
-	let candidates = vec![1, 2, 3];
-	let voters = vec![
-		(10, 100, vec![1, 1, 2, 3]),
-		(20, 100, vec![2, 3]),
-		(30, 50, vec![1, 1, 2]),
+fn support_map_is_independent_of_assignment_order() {
+	let winners = vec![10, 20];
+	let assignments = vec![
+		StakedAssignment { who: 3, distribution: vec![(10, 5), (20, 5)] },
+		StakedAssignment { who: 1, distribution: vec![(10, 10)] },
+		StakedAssignment { who: 2, distribution: vec![(20, 7), (10, 3)] },
 	];
+	let mut permuted = assignments.clone();
+	permuted.swap(0, 2);
+	permuted.swap(1, 2);
 
-	let ElectionResult { winners, assignments } = seq_phragmen::<_, Perbill>(
-		2,
-		candidates,
-		voters,
-		None,
-	).unwrap();
-	let winners = to_without_backing(winners);
+	let supports = build_support_map(&winners, &assignments).unwrap();
+	assert_eq!(supports, build_support_map(&winners, &permuted).unwrap());
+	assert_eq!(
+		supports.get(&10).unwrap(),
+		&Support { total: 18, voters: vec![(1, 10), (2, 3), (3, 5)] },
+	);
+	assert_eq!(supports.get(&20).unwrap(), &Support { total: 12, voters: vec![(2, 7), (3, 5)] });
+}
+
+#[test]
+fn seq_phragmen_is_independent_of_voter_order() {
+	let candidates = vec![1, 2, 3, 4, 5];
+	let voters = vec![
+		(10, 10, vec![1, 2]),
+		(20, 20, vec![1, 3]),
+		(30, 30, vec![1, 2, 3, 4]),
+		(40, 40, vec![1, 3, 4, 5]),
+		(50, 50, vec![2, 4, 5]),
+		(60, 25, vec![5]),
+	];
+	let sorted = |mut assignments: Vec<Assignment<AccountId, Perbill>>| {
+		assignments.sort_by_key(|a| a.who);
+		assignments
+	};
+
+	// balancing walks the voters in order, hence the rounding of its outcome may differ.
+	let result = seq_phragmen::<_, Perbill>(3, candidates.clone(), voters.clone(), None).unwrap();
+	for rotation in 1..voters.len() {
+		let mut permuted = voters.clone();
+		permuted.rotate_left(rotation);
+		permuted.swap(0, 2);
+		let other = seq_phragmen::<_, Perbill>(3, candidates.clone(), permuted, None).unwrap();
+		assert_eq!(other.winners, result.winners);
+		assert_eq!(sorted(other.assignments), sorted(result.assignments.clone()));
+	}
+}
+
+#[test]
+fn reduce_is_independent_of_assignment_order() {
+	let assignments = vec![
+		StakedAssignment { who: 1, distribution: vec![(10, 10)] },
+		StakedAssignment { who: 2, distribution: vec![(10, 15), (20, 5)] },
+		StakedAssignment { who: 3, distribution: vec![(20, 15), (40, 15)] },
+		StakedAssignment { who: 4, distribution: vec![(20, 10), (30, 10), (40, 20)] },
+		StakedAssignment { who: 5, distribution: vec![(20, 20), (30, 10), (40, 20)] },
+	];
+	let edges_of = |assignments: &[StakedAssignment<AccountId>]| {
+		let mut edges = assignments
+			.iter()
+			.flat_map(|a| a.distribution.iter().map(move |(t, w)| (a.who, *t, *w)))
+			.collect::<Vec<_>>();
+		edges.sort();
+		edges
+	};
+
+	let mut reduced = assignments.clone();
+	reduce(&mut reduced);
+	let expected = vec![
+		(1, 10, 10),
+		(2, 10, 15),
+		(2, 20, 5),
+		(3, 20, 30),
+		(4, 40, 40),
+		(5, 20, 15),
+		(5, 30, 20),
+		(5, 40, 15),
+	];
+	assert_eq!(edges_of(&reduced), expected);
+	for rotation in 0..assignments.len() {
+		let mut permuted = assignments.clone();
+		permuted.rotate_left(rotation);
+		permuted.swap(0, 1);
+		permuted.iter_mut().for_each(|a| a.distribution.reverse());
+		let order = permuted.iter().map(|a| a.who).collect::<Vec<_>>();
+
+		reduce(&mut permuted);
+		assert_eq!(edges_of(&permuted), expected);
+		// the order of the voters, and that of their targets, is kept.
+		assert_eq!(permuted.iter().map(|a| a.who).collect::<Vec<_>>(), order);
+		let five = permuted.iter().find(|a| a.who == 5).unwrap();
+		assert_eq!(five.distribution, vec![(40, 15), (30, 20), (20, 15)]);
+	}
+}
+
+#[test]
+fn duplicate_target_is_ignored() {
+	let candidates = vec![1, 2, 3];
+	let voters = vec![
+		(10, 100, vec![1, 1, 2, 3]),
+		(20, 100, vec![2, 3]),
+		(30, 50, vec![1, 1, 2]),
+	];
+
+	let ElectionResult { winners, assignments } = seq_phragmen::<_, Perbill>(
+		2,
+		candidates,
+		voters,
+		None,
+	).unwrap();
+	let winners = to_without_backing(winners);
 
 	assert_eq!(winners, vec![(2), (3)]);
 	assert_eq!(
@@ -799,6 +1539,899 @@ fn duplicate_target_is_ignored_when_winner() {
 	);
 }
 
+#[test]
+fn support_concentration_risk_works() {
+	let dominated = Support::<AccountId> { total: 100, voters: vec![(1, 90), (2, 5), (3, 5)] };
+	assert_eq!(dominated.concentration_risk(), Perbill::from_percent(90));
+
+	let spread = Support::<AccountId> { total: 100, voters: vec![(1, 25), (2, 25), (3, 50)] };
+	assert_eq!(spread.concentration_risk(), Perbill::from_percent(50));
+	assert!(spread.concentration_risk() < dominated.concentration_risk());
+
+	let empty = Support::<AccountId> { total: 0, voters: vec![] };
+	assert_eq!(empty.concentration_risk(), Perbill::zero());
+}
+
+#[test]
+fn support_new_checked_works() {
+	let support = Support::<AccountId>::new_checked(100, vec![(1, 90), (2, 10)]).unwrap();
+	assert_eq!(support, Support { total: 100, voters: vec![(1, 90), (2, 10)] });
+	assert_eq!(Support::<AccountId>::new_checked(0, vec![]).unwrap(), Support::default());
+
+	assert_eq!(
+		Support::<AccountId>::new_checked(100, vec![(1, 90), (2, 5)]),
+		Err("total does not match the sum of voter weights"),
+	);
+	assert_eq!(
+		Support::<AccountId>::new_checked(0, vec![(1, ExtendedBalance::MAX), (2, 1)]),
+		Err("sum of voter weights overflows"),
+	);
+}
+
+mod brute_force {
+	use super::*;
+	use crate::phragmms;
+
+	/// To be elected count, candidates and voters of an election.
+	type Fixture = (usize, Vec<AccountId>, Vec<(AccountId, VoteWeight, Vec<AccountId>)>);
+
+	/// The fixtures on which seq-phragmen and phragmms are compared against the optimal maximin
+	/// support, found by brute force.
+	///
+	/// PhragMMS provides a factor 2 approximation of the maximin support, thus the committee that it
+	/// elects must have a maximin support of at least half of the optimal. Seq-phragmen provides no
+	/// such guarantee in general, yet, it is held to the same bound on these small fixtures.
+	fn fixtures() -> Vec<Fixture> {
+		vec![
+			(
+				3,
+				vec![1, 2, 3, 4],
+				vec![
+					(10, 10, vec![1, 2]),
+					(20, 20, vec![1, 3]),
+					(30, 30, vec![2, 3]),
+					(40, 40, vec![3, 4]),
+				],
+			),
+			(
+				3,
+				vec![1, 2, 3, 4],
+				vec![
+					(10, 100, vec![1]),
+					(20, 50, vec![1, 2]),
+					(30, 50, vec![2, 3, 4]),
+					(40, 15, vec![4]),
+					(50, 60, vec![1, 4]),
+				],
+			),
+			(
+				2,
+				vec![1, 2, 3, 4],
+				vec![
+					(10, 1000, vec![1, 2, 3, 4]),
+					(20, 10, vec![3]),
+					(30, 10, vec![4]),
+				],
+			),
+		]
+	}
+
+	#[test]
+	fn brute_force_works() {
+		let voters = vec![
+			(10, 10, vec![1]),
+			(20, 20, vec![2]),
+			(30, 30, vec![3]),
+		];
+		assert_eq!(brute_force_maximin(2, &[1, 2, 3], &voters), (vec![2, 3], 20));
+		assert_eq!(maximin_support_of(&[1, 2], &[1, 2, 3], &voters), 10);
+	}
+
+	#[test]
+	fn seq_phragmen_is_within_bound_of_optimal() {
+		for (to_elect, candidates, voters) in fixtures() {
+			let (_, optimal) = brute_force_maximin(to_elect, &candidates, &voters);
+			let ElectionResult { winners, .. } = seq_phragmen::<_, Perbill>(
+				to_elect,
+				candidates.clone(),
+				voters.clone(),
+				None,
+			).unwrap();
+			let winners = to_without_backing(winners);
+			let achieved = maximin_support_of(&winners, &candidates, &voters);
+
+			assert!(achieved <= optimal);
+			assert!(
+				achieved * 2 >= optimal,
+				"seq-phragmen elected {:?} with {} against the optimal {}",
+				winners,
+				achieved,
+				optimal,
+			);
+		}
+	}
+
+	#[test]
+	fn phragmms_is_within_bound_of_optimal() {
+		for (to_elect, candidates, voters) in fixtures() {
+			let (_, optimal) = brute_force_maximin(to_elect, &candidates, &voters);
+			let ElectionResult { winners, .. } = phragmms::<_, Perbill>(
+				to_elect,
+				candidates.clone(),
+				voters.clone(),
+				Some((10, 0)),
+			).unwrap();
+			let winners = to_without_backing(winners);
+			let achieved = maximin_support_of(&winners, &candidates, &voters);
+
+			assert!(achieved <= optimal);
+			assert!(
+				achieved * 2 >= optimal,
+				"phragmms elected {:?} with {} against the optimal {}",
+				winners,
+				achieved,
+				optimal,
+			);
+		}
+	}
+}
+
+#[test]
+fn edge_slack_detects_fragile_edges() {
+	let candidates = vec![1, 2, 3];
+	let voters = vec![
+		(10, 10, vec![1]),
+		(20, 11, vec![2]),
+		(30, 50, vec![3]),
+	];
+
+	let result = seq_phragmen::<_, Perbill>(2, candidates.clone(), voters.clone(), None).unwrap();
+	assert_eq!(to_without_backing(result.winners.clone()), vec![3, 2]);
+
+	let slacks = edge_slack(&result, candidates, voters).unwrap();
+	assert_eq!(slacks.len(), 2);
+
+	// 2 barely beats 1, any decrease would be a tie, which 2 loses.
+	let fragile = *slacks.get(&(20, 2)).unwrap();
+	// 3 can lose a lot of stake before being out-staked by 1.
+	let robust = *slacks.get(&(30, 3)).unwrap();
+
+	assert_eq!(fragile, 0);
+	assert_eq!(robust, 39);
+	assert!(fragile < robust);
+}
+
+#[test]
+fn min_voter_flip_is_found_on_fragile_result() {
+	let candidates = vec![1, 2, 3];
+	let voters = vec![(10, 100, vec![1]), (20, 100, vec![2]), (30, 95, vec![3]), (40, 10, vec![1])];
+	let result = seq_phragmen::<_, Perbill>(2, candidates.clone(), voters.clone(), None).unwrap();
+	assert_eq_uvec!(to_without_backing(result.winners.clone()), vec![1, 2]);
+
+	// 40 is the smallest voter, and moving its vote from 1 to 3 is enough to elect 3.
+	assert_eq!(
+		min_voter_flip_to_change_winners(&result, candidates, voters).unwrap(),
+		Some((40, 1, 3)),
+	);
+}
+
+#[test]
+fn min_voter_flip_is_none_on_robust_result() {
+	let candidates = vec![1, 2, 3];
+	let voters = (1..=10u64)
+		.map(|v| (v, 10, vec![1]))
+		.chain((11..=20).map(|v| (v, 10, vec![2])))
+		.chain(std::iter::once((21, 1, vec![3])))
+		.collect::<Vec<_>>();
+	let result = seq_phragmen::<_, Perbill>(2, candidates.clone(), voters.clone(), None).unwrap();
+	assert_eq_uvec!(to_without_backing(result.winners.clone()), vec![1, 2]);
+
+	assert_eq!(min_voter_flip_to_change_winners(&result, candidates, voters).unwrap(), None);
+}
+
+#[test]
+fn stake_to_unseat_works() {
+	let candidates = vec![10, 20, 30];
+	let voters = vec![
+		(1, 1_000, vec![10]),
+		(2, 40, vec![10, 30]),
+		(3, 60, vec![20]),
+	];
+	let result = seq_phragmen::<_, Perbill>(2, candidates.clone(), voters.clone(), None).unwrap();
+	assert_eq!(to_without_backing(result.winners.clone()), vec![10, 20]);
+
+	// 20 is marginal: 30 needs a bit more than 20 to beat it in the second round.
+	let tipping = stake_to_unseat(&20, &result, candidates.clone(), voters.clone())
+		.unwrap()
+		.unwrap();
+	assert_eq!(tipping, 23);
+	let mut boosted = voters.clone();
+	boosted.push((30, 22, vec![30]));
+	let kept = seq_phragmen::<_, Perbill>(2, candidates.clone(), boosted.clone(), None).unwrap();
+	assert_eq!(to_without_backing(kept.winners), vec![10, 20]);
+	boosted.last_mut().unwrap().1 = 23;
+	let unseated = seq_phragmen::<_, Perbill>(2, candidates.clone(), boosted, None).unwrap();
+	assert_eq!(to_without_backing(unseated.winners), vec![10, 30]);
+
+	// 10 is dominant: electing 30 always displaces 20 instead.
+	assert_eq!(stake_to_unseat(&10, &result, candidates.clone(), voters.clone()).unwrap(), None);
+
+	// 30 is not a winner.
+	assert_eq!(stake_to_unseat(&30, &result, candidates.clone(), voters.clone()).unwrap(), None);
+
+	// with more votes than `result` was computed from, 30 already beats 20.
+	let mut more_voters = voters;
+	more_voters.push((4, 40, vec![30]));
+	assert_eq!(stake_to_unseat(&20, &result, candidates, more_voters).unwrap(), Some(0));
+}
+
+#[test]
+fn kingmaker_voters_works() {
+	let candidates = vec![10, 20, 30];
+	let voters = vec![
+		(1, 100, vec![10]),
+		(5, 100, vec![10]),
+		(2, 50, vec![20]),
+		(4, 10, vec![20]),
+		(3, 45, vec![30]),
+	];
+	let result = seq_phragmen::<_, Perbill>(2, candidates.clone(), voters.clone(), None).unwrap();
+	assert_eq!(to_without_backing(result.winners.clone()), vec![10, 20]);
+
+	// without 2, 30 beats 20. 10 has a backup in any case, and 20 beats 30 without 4 as well.
+	assert_eq!(kingmaker_voters(&result, candidates.clone(), voters.clone()).unwrap(), vec![2]);
+
+	// once 10 has a single voter, that one is a kingmaker as well.
+	let voters = voters.into_iter().filter(|(who, _, _)| *who != 5).collect::<Vec<_>>();
+	assert_eq!(kingmaker_voters(&result, candidates, voters).unwrap(), vec![1, 2]);
+}
+
+#[test]
+fn staking_election_pipeline_works() {
+	let candidates = vec![1, 2, 3, 4, 5];
+	let voters = vec![
+		(10, vec![1, 2]),
+		(20, vec![1, 3]),
+		(30, vec![1, 2, 3, 4]),
+		(40, vec![1, 3, 4, 5]),
+		(50, vec![2, 4, 5]),
+	];
+	let stake_of = create_stake_of(&[(10, 10), (20, 20), (30, 30), (40, 40), (50, 50)]);
+
+	let (result, support_map, score) = run_staking_election::<_, Perbill, _>(
+		candidates.clone(),
+		voters.clone(),
+		&stake_of,
+		4,
+		4,
+	).unwrap();
+
+	// manually chain each step.
+	let ElectionResult { winners, assignments } = seq_phragmen::<_, Perbill>(
+		4,
+		candidates,
+		voters.iter().map(|(ref v, ref vs)| (v.clone(), stake_of(v), vs.clone())).collect::<Vec<_>>(),
+		Some((4, 0)),
+	).unwrap();
+	let mut staked = assignment_ratio_to_staked_normalized(assignments, &stake_of).unwrap();
+	reduce(&mut staked);
+	let manual_support_map = build_support_map(&to_without_backing(winners.clone()), &staked).unwrap();
+	let manual_score = evaluate_support(&manual_support_map);
+	let manual_assignments = assignment_staked_to_ratio_normalized::<_, Perbill>(staked).unwrap();
+
+	assert_eq!(result.winners, winners);
+	assert_eq!(result.assignments, manual_assignments);
+	assert_eq!(support_map, manual_support_map);
+	assert_eq!(score, manual_score);
+	assert_eq!(score, evaluate_support(&support_map));
+}
+
+#[test]
+fn elect_with_and_without_balancing_works() {
+	let candidates = vec![1, 2, 3, 4, 5];
+	let voters = vec![
+		(10, 10, vec![1, 2]),
+		(20, 20, vec![1, 3]),
+		(30, 30, vec![1, 2, 3, 4]),
+		(40, 40, vec![1, 3, 4, 5]),
+		(50, 50, vec![2, 4, 5]),
+	];
+	let stake_of = create_stake_of(&[(10, 10), (20, 20), (30, 30), (40, 40), (50, 50)]);
+	let score_of = |result: &ElectionResult<AccountId, Perbill>| {
+		let staked = assignment_ratio_to_staked_normalized(result.assignments.clone(), &stake_of)
+			.unwrap();
+		let winners = to_without_backing(result.winners.clone());
+		evaluate_support(&build_support_map(&winners, &staked).unwrap())
+	};
+
+	let (unbalanced, balanced, improvement) = elect_with_and_without_balancing::<_, Perbill>(
+		4,
+		candidates.clone(),
+		voters.clone(),
+		10,
+	).unwrap();
+
+	// each of them is the same as the corresponding plain election.
+	let plain = seq_phragmen::<_, Perbill>(4, candidates.clone(), voters.clone(), Some((10, 0)))
+		.unwrap();
+	assert_eq!(balanced.winners, plain.winners);
+	assert_eq!(balanced.assignments, plain.assignments);
+
+	// balancing helps here, and the balanced score is never worse.
+	let (before, after) = (score_of(&unbalanced), score_of(&balanced));
+	assert!(!is_score_better(before, after, Perbill::zero()));
+	assert!(is_score_better(after, before, Perbill::zero()));
+	assert_eq!(improvement, [after[0] - before[0], after[1] - before[1], before[2] - after[2]]);
+	assert!(improvement[0] > 0);
+
+	// no iterations at all is no balancing.
+	let (unbalanced, balanced, improvement) =
+		elect_with_and_without_balancing::<_, Perbill>(4, candidates, voters, 0).unwrap();
+	assert_eq!(balanced.winners, unbalanced.winners);
+	assert_eq!(balanced.assignments, unbalanced.assignments);
+	assert_eq!(improvement, [0, 0, 0]);
+}
+
+#[test]
+fn election_summary_works() {
+	let candidates = vec![1, 2, 3, 4, 5];
+	let voters = vec![
+		(10, 30, vec![1]),
+		(20, 25, vec![2]),
+		(30, 25, vec![3]),
+		(40, 20, vec![4]),
+		(50, 5, vec![5]),
+	];
+	let stake_of = |who: &AccountId| voters.iter().find(|(v, _, _)| v == who).unwrap().1;
+
+	let result = seq_phragmen::<_, Perbill>(4, candidates.clone(), voters.clone(), None).unwrap();
+	let staked = assignment_ratio_to_staked(result.assignments.clone(), stake_of);
+	let support_map = build_support_map(&to_without_backing(result.winners.clone()), &staked)
+		.unwrap();
+
+	let summary = summarize(&result, &support_map, candidates.clone(), voters.clone()).unwrap();
+	let winners = to_without_backing(result.winners.clone());
+	let [min_support, total_stake, _] = evaluate_support(&support_map);
+	assert_eq!(summary.min_support, min_support);
+	assert_eq!(summary.min_support, min_support_of_winners(&support_map, &winners));
+	assert_eq!(summary.total_stake, total_stake);
+	assert_eq!(summary.nakamoto_coefficient, nakamoto_coefficient(&support_map));
+	assert_eq!(summary.entry_threshold, entry_threshold(candidates, voters.clone(), 4).unwrap());
+	assert_eq!(
+		summary,
+		ElectionSummary {
+			winner_count: 4,
+			total_stake: 100,
+			min_support: 20,
+			max_support: 30,
+			mean_support: 25,
+			// 30 is not more than a third, 30 + 25 is.
+			nakamoto_coefficient: 2,
+			entry_threshold: 20,
+		},
+	);
+
+	// empty elections are all zero.
+	let empty = seq_phragmen::<AccountId, Perbill>(0, vec![], vec![], None).unwrap();
+	assert_eq!(
+		summarize(&empty, &Default::default(), vec![], vec![]).unwrap(),
+		ElectionSummary::default(),
+	);
+}
+
+#[test]
+fn election_summary_of_balanced_election_works() {
+	let candidates = vec![1, 2, 3, 4];
+	let voters = vec![
+		(10, 10, vec![1, 2]),
+		(20, 20, vec![1, 3]),
+		(30, 30, vec![2, 3, 4]),
+		(40, 15, vec![4]),
+	];
+	let stake_of = |who: &AccountId| voters.iter().find(|(v, _, _)| v == who).unwrap().1;
+
+	let result = seq_phragmen::<_, Perbill>(3, candidates.clone(), voters.clone(), Some((10, 0)))
+		.unwrap();
+	let staked = assignment_ratio_to_staked(result.assignments.clone(), stake_of);
+	let mut support_map = build_support_map(&to_without_backing(result.winners.clone()), &staked)
+		.unwrap();
+	let summary = ElectionSummary {
+		winner_count: 3,
+		total_stake: 75,
+		min_support: 24,
+		max_support: 26,
+		mean_support: 25,
+		// 26 is more than a third.
+		nakamoto_coefficient: 1,
+		// balancing has raised the weakest winner above the unbalanced threshold.
+		entry_threshold: 21,
+	};
+	let summary_of = |support_map: &SupportMap<AccountId>| {
+		summarize(&result, support_map, candidates.clone(), voters.clone()).unwrap()
+	};
+	assert_eq!(summary_of(&support_map), summary);
+	assert_eq!(
+		summary.entry_threshold,
+		entry_threshold(candidates.clone(), voters.clone(), 3).unwrap(),
+	);
+
+	// supports of anyone but the winners are ignored.
+	support_map.insert(2, Support { total: 1000, voters: vec![(30, 1000)] });
+	assert_eq!(summary_of(&support_map), summary);
+
+	// a winner without support counts as zero, and still counts towards the mean.
+	support_map.remove(&1);
+	assert_eq!(
+		summary_of(&support_map),
+		ElectionSummary {
+			total_stake: 51,
+			min_support: 0,
+			mean_support: 17,
+			..summary
+		},
+	);
+	assert_eq!(min_support_of_winners(&support_map, &[1, 3, 4]), 0);
+}
+
+#[test]
+fn election_result_edges_works() {
+	let candidates = vec![1, 2, 3];
+	let voters = vec![(10, 100, vec![1, 2]), (20, 200, vec![2, 3]), (30, 300, vec![1, 3])];
+	let stake_of = |who: &AccountId| voters.iter().find(|(v, _, _)| v == who).unwrap().1;
+	let result = seq_phragmen::<_, Perbill>(2, candidates, voters.clone(), None).unwrap();
+
+	let edges = result.edges(stake_of).collect::<Vec<_>>();
+	assert_eq!(
+		edges.len(),
+		result.assignments.iter().map(|a| a.distribution.len()).sum::<usize>(),
+	);
+	assert_eq!(
+		edges.iter().map(|(_, _, w)| w).sum::<ExtendedBalance>(),
+		voters.iter().map(|(_, s, _)| *s as ExtendedBalance).sum::<ExtendedBalance>(),
+	);
+
+	let staked = assignment_ratio_to_staked(result.assignments.clone(), stake_of);
+	assert_eq!(
+		edges,
+		staked
+			.into_iter()
+			.flat_map(|a| {
+				let who = a.who;
+				a.distribution.into_iter().map(move |(t, w)| (who, t, w))
+			})
+			.collect::<Vec<_>>(),
+	);
+}
+
+#[test]
+fn compress_shared_works() {
+	use codec::Encode;
+
+	let candidates = (1..=8).collect::<Vec<AccountId>>();
+	let voters = (100..200)
+		.map(|v| (v, 100, candidates.clone()))
+		.collect::<Vec<(AccountId, VoteWeight, Vec<AccountId>)>>();
+	let result = seq_phragmen::<_, Perbill>(8, candidates, voters, None).unwrap();
+	let winners = result.winners.clone();
+	let assignments = result.assignments.clone();
+	let original_size = (&winners, &assignments).encode().len();
+
+	let compressed = result.compress_shared();
+	assert_eq!(compressed.groups.len(), 1);
+	assert!(compressed.encode().len() * 2 < original_size);
+
+	let decompressed = compressed.decompress().unwrap();
+	assert_eq!(decompressed.winners, winners);
+	assert_eq!(decompressed.assignments, assignments);
+}
+
+#[test]
+fn decompress_rejects_invalid_groups() {
+	let compressed = CompressedResult::<AccountId, Perbill> {
+		winners: vec![(1, 10), (2, 10)],
+		groups: vec![vec![1, 2]],
+		assignments: vec![
+			CompressedAssignment { who: 10, group: 0, ratios: vec![Perbill::from_percent(50); 2] },
+		],
+	};
+	assert!(compressed.clone().decompress().is_ok());
+
+	let mut unknown_group = compressed.clone();
+	unknown_group.assignments[0].group = 1;
+	assert_eq!(unknown_group.decompress().unwrap_err(), Error::CompactInvalidIndex);
+
+	let mut missing_ratio = compressed;
+	missing_ratio.assignments[0].ratios.pop();
+	assert_eq!(missing_ratio.decompress().unwrap_err(), Error::CompactInvalidIndex);
+}
+
+#[test]
+fn to_staking_storage_format_works() {
+	let result = ElectionResult::<AccountId, Perbill> {
+		winners: vec![(20, 60), (10, 120)],
+		assignments: vec![
+			Assignment { who: 10, distribution: vec![(10, Perbill::one())] },
+			Assignment {
+				who: 1,
+				distribution: vec![
+					(10, Perbill::from_percent(40)),
+					(20, Perbill::from_percent(60)),
+				],
+			},
+			Assignment { who: 20, distribution: vec![(20, Perbill::one())] },
+		],
+	};
+	let stake_of = create_stake_of(&[(10, 100), (1, 50), (20, 30)]);
+
+	let (validators, exposures) = result.to_staking_storage_format(&stake_of).unwrap();
+	assert_eq!(validators, vec![20, 10]);
+	assert_eq!(
+		exposures,
+		vec![
+			(10, Exposure {
+				total: 120,
+				own: 100,
+				others: vec![IndividualExposure { who: 1, value: 20 }],
+			}),
+			(20, Exposure {
+				total: 60,
+				own: 30,
+				others: vec![IndividualExposure { who: 1, value: 30 }],
+			}),
+		],
+	);
+}
+
+#[test]
+fn cross_check_works() {
+	let candidates = vec![1, 2, 3];
+	let voters = vec![(10, 10, vec![1, 2]), (20, 20, vec![2, 3]), (30, 15, vec![3])];
+	let result = || seq_phragmen::<_, Perbill>(2, candidates.clone(), voters.clone(), None)
+		.unwrap();
+
+	// identical results agree, regardless of the order of winners and assignments.
+	let mut shuffled = result();
+	shuffled.winners.reverse();
+	shuffled.assignments.reverse();
+	shuffled.assignments.iter_mut().for_each(|a| a.distribution.reverse());
+	let report = cross_check(&result(), &shuffled);
+	assert!(report.agrees());
+	assert_eq!(report, CrossCheckReport::default());
+
+	// different winners disagree.
+	let mut other = result();
+	other.winners[0].0 = 1;
+	let report = cross_check(&result(), &other);
+	assert!(!report.winners_agree());
+	assert!(!report.agrees());
+	assert_eq!(report.winners_only_in_a, vec![to_without_backing(result().winners)[0]]);
+	assert_eq!(report.winners_only_in_b, vec![1]);
+
+	// same winners, but a voter is assigned differently.
+	let mut other = result();
+	let voter = other.assignments[0].who;
+	other.assignments[0].distribution = vec![(3, Perbill::one())];
+	let report = cross_check(&result(), &other);
+	assert!(report.winners_agree());
+	assert!(!report.agrees());
+	assert_eq!(report.diverging_voters, vec![voter]);
+}
+
+#[test]
+fn winner_explanations_work() {
+	let candidates = vec![1, 2];
+	let voters = vec![
+		(10, 10, vec![1]),
+		(20, 20, vec![1]),
+		(30, 30, vec![1]),
+		(40, 40, vec![1]),
+		(50, 5, vec![2]),
+	];
+	let stake_of = create_stake_of(&[(10, 10), (20, 20), (30, 30), (40, 40), (50, 5)]);
+
+	let result = seq_phragmen::<_, Perbill>(2, candidates, voters, None).unwrap();
+	let staked = assignment_ratio_to_staked(result.assignments.clone(), &stake_of);
+	let support_map = build_support_map(&to_without_backing(result.winners.clone()), &staked).unwrap();
+
+	let explanations = result.explanations(&support_map);
+	assert_eq!(explanations.len(), 2);
+	assert_eq!(
+		explanations.get(&1).unwrap(),
+		&WinnerExplanation {
+			round: 0,
+			backing: 100,
+			support: 100,
+			// 10 is the fourth backer and is not listed.
+			top_backers: vec![(40, 40), (30, 30), (20, 20)],
+		},
+	);
+	assert_eq!(
+		explanations.get(&2).unwrap(),
+		&WinnerExplanation { round: 1, backing: 5, support: 5, top_backers: vec![(50, 5)] },
+	);
+}
+
+#[test]
+fn incremental_election_converges_to_seq_phragmen() {
+	let candidates = vec![1, 2, 3, 4];
+	let votes = vec![
+		(10, 10, vec![1, 2]),
+		(20, 20, vec![1, 3]),
+		(30, 30, vec![2, 3]),
+		(40, 15, vec![4, 4, 5]),
+	];
+
+	let mut election = IncrementalElection::new(2, candidates.clone());
+	assert_eq!(election.current_winners(), vec![(1, 0), (2, 0)]);
+
+	let mut provisional = vec![];
+	for (voter, stake, targets) in votes.clone() {
+		election.add_vote(voter, targets, stake);
+		provisional.push(election.current_winners());
+	}
+
+	assert_eq!(
+		provisional,
+		vec![
+			vec![(1, 10), (2, 10)],
+			vec![(1, 30), (3, 20)],
+			vec![(3, 50), (2, 40)],
+			vec![(3, 50), (2, 40)],
+		],
+	);
+	assert_eq!(election.vote_count(), 4);
+
+	let full = seq_phragmen::<_, Perbill>(2, candidates, votes, None).unwrap();
+	let incremental = election.result::<Perbill>(None).unwrap();
+
+	let mut provisional_winners = to_without_backing(election.current_winners());
+	let mut full_winners = to_without_backing(full.winners.clone());
+	provisional_winners.sort();
+	full_winners.sort();
+	assert_eq!(provisional_winners, full_winners);
+	assert_eq!(incremental.winners, full.winners);
+	assert_eq!(incremental.assignments, full.assignments);
+
+	// a new vote of an existing voter replaces its old vote.
+	election.add_vote(40, vec![1], 25);
+	assert_eq!(election.vote_count(), 4);
+	assert_eq!(election.current_winners(), vec![(1, 55), (3, 50)]);
+}
+
+#[test]
+fn incremental_election_may_not_converge_to_seq_phragmen() {
+	let mut election = IncrementalElection::new(2, vec![1, 2, 3]);
+	election.add_vote(10, vec![1, 2], 100);
+	election.add_vote(20, vec![3], 60);
+
+	// 1 and 2 have the highest approval stake, but once 1 is elected, the stake of 10 is used up.
+	assert_eq!(election.current_winners(), vec![(1, 100), (2, 100)]);
+	let result = election.result::<Perbill>(None).unwrap();
+	assert_eq!(to_without_backing(result.winners), vec![1, 3]);
+}
+
+#[test]
+fn support_map_keep_top_works() {
+	let support_of = |total: ExtendedBalance| Support { total, voters: vec![(100, total)] };
+	let mut support_map: SupportMap<u64> = vec![
+		(1, support_of(10)),
+		(2, support_of(50)),
+		(3, support_of(30)),
+		(4, support_of(20)),
+		(5, support_of(30)),
+	].into_iter().collect();
+
+	// 3 and 5 tie, both survive.
+	support_map.keep_top(3);
+	assert_eq!(support_map.keys().cloned().collect::<Vec<_>>(), vec![2, 3, 5]);
+	assert_eq!(support_map.get(&2).unwrap(), &support_of(50));
+
+	// ties are broken by id.
+	support_map.keep_top(2);
+	assert_eq!(support_map.keys().cloned().collect::<Vec<_>>(), vec![2, 3]);
+
+	support_map.keep_top(5);
+	assert_eq!(support_map.len(), 2);
+	support_map.keep_top(0);
+	assert!(support_map.is_empty());
+}
+
+#[test]
+fn effective_validator_count_works() {
+	use sp_arithmetic::{FixedU128, FixedPointNumber};
+
+	let support_map_of = |totals: Vec<ExtendedBalance>| {
+		totals
+			.into_iter()
+			.enumerate()
+			.map(|(who, total)| (who as AccountId, Support { total, voters: vec![] }))
+			.collect::<SupportMap<AccountId>>()
+	};
+
+	let equal = support_map_of(vec![100; 10]);
+	assert_eq!(equal.effective_validator_count(), FixedU128::saturating_from_integer(10));
+
+	let mut dominated = vec![1; 9];
+	dominated.push(1_000_000);
+	let count = support_map_of(dominated).effective_validator_count();
+	assert!(count >= FixedU128::one());
+	assert!(count < FixedU128::saturating_from_rational(10_001, 10_000));
+
+	assert_eq!(support_map_of(vec![]).effective_validator_count(), FixedU128::zero());
+	assert_eq!(support_map_of(vec![0, 0]).effective_validator_count(), FixedU128::zero());
+}
+
+#[test]
+fn support_map_apply_delta_works() {
+	let winners = vec![10, 20, 30];
+	let staked = |distributions: Vec<(AccountId, Vec<(AccountId, ExtendedBalance)>)>| {
+		distributions
+			.into_iter()
+			.map(|(who, distribution)| StakedAssignment { who, distribution })
+			.collect::<Vec<_>>()
+	};
+
+	let before = staked(vec![
+		(1, vec![(10, 40), (20, 60)]),
+		(2, vec![(20, 30)]),
+		(3, vec![(10, 5), (30, 15)]),
+	]);
+	// 1 moves all of its stake from 10 to 20, 3 moves part of its stake from 30 to 20.
+	let after = staked(vec![
+		(1, vec![(20, 100)]),
+		(2, vec![(20, 30)]),
+		(3, vec![(10, 5), (20, 10), (30, 5)]),
+	]);
+
+	let mut support_map = build_support_map(&winners, &before).unwrap();
+	support_map.apply_delta(&[(1, 10, 40), (3, 30, 10)], &[(1, 20, 40), (3, 20, 10)]).unwrap();
+	assert_eq!(support_map, build_support_map(&winners, &after).unwrap());
+
+	// unknown targets and missing edges are reported, and nothing is applied.
+	let unchanged = build_support_map(&winners, &after).unwrap();
+	assert_eq!(support_map.apply_delta(&[], &[(1, 40, 10)]), Err(40));
+	assert_eq!(support_map, unchanged);
+	assert_eq!(support_map.apply_delta(&[(2, 10, 1)], &[]), Err(10));
+	assert_eq!(support_map, unchanged);
+	assert_eq!(support_map.apply_delta(&[(3, 30, 6)], &[]), Err(30));
+	assert_eq!(support_map, unchanged);
+	// the edges before the failing one are not applied either.
+	assert_eq!(support_map.apply_delta(&[(1, 20, 50), (2, 30, 1)], &[]), Err(30));
+	assert_eq!(support_map, unchanged);
+	assert_eq!(support_map.apply_delta(&[(1, 20, 50)], &[(1, 10, 50), (1, 40, 1)]), Err(40));
+	assert_eq!(support_map, unchanged);
+	// removing the same edge twice is more than it has.
+	assert_eq!(support_map.apply_delta(&[(3, 30, 3), (3, 30, 3)], &[]), Err(30));
+	assert_eq!(support_map, unchanged);
+}
+
+#[test]
+fn commission_adjusted_score_works() {
+	let support_map_of = |totals: Vec<(AccountId, ExtendedBalance)>| {
+		totals
+			.into_iter()
+			.map(|(who, total)| (who, Support { total, voters: vec![(100, total)] }))
+			.collect::<SupportMap<AccountId>>()
+	};
+	let commission_of = |who: &AccountId| if *who == 10 { Perbill::from_percent(50) } else {
+		Perbill::zero()
+	};
+
+	// same raw score, neither one is better.
+	let a = support_map_of(vec![(10, 50), (20, 100)]);
+	let b = support_map_of(vec![(10, 100), (20, 50)]);
+	assert_eq!(evaluate_support(&a), evaluate_support(&b));
+	assert!(!is_score_better(evaluate_support(&a), evaluate_support(&b), Perbill::zero()));
+	assert!(!is_score_better(evaluate_support(&b), evaluate_support(&a), Perbill::zero()));
+
+	// the commission of 10 halves its effective support, which makes the minimum of `a` worse.
+	let a_score = commission_adjusted_score(&a, commission_of);
+	let b_score = commission_adjusted_score(&b, commission_of);
+	assert_eq!(a_score, [25, 125, 25 * 25 + 100 * 100]);
+	assert_eq!(b_score, [50, 100, 50 * 50 + 50 * 50]);
+	assert!(is_score_better(b_score, a_score, Perbill::zero()));
+	assert!(!is_score_better(a_score, b_score, Perbill::zero()));
+
+	// with no commission at all, the score is the same as the raw one.
+	assert_eq!(commission_adjusted_score(&a, |_| Perbill::zero()), evaluate_support(&a));
+}
+
+#[test]
+fn reciprocity_report_works() {
+	let assignments = vec![
+		// 1 and 2 back each other.
+		StakedAssignment { who: 1, distribution: vec![(2, 10), (3, 5)] },
+		StakedAssignment { who: 2, distribution: vec![(1, 10)] },
+		// 3 backs 4, but 4 backs 3 with no stake.
+		StakedAssignment { who: 3, distribution: vec![(4, 10)] },
+		StakedAssignment { who: 4, distribution: vec![(3, 0)] },
+		// self votes are not mutual.
+		StakedAssignment { who: 5, distribution: vec![(5, 10)] },
+	];
+
+	assert_eq!(reciprocity_report(&assignments), vec![(1, 2), (2, 1)]);
+}
+
+#[test]
+fn apply_slash_cascades_to_other_supports() {
+	let support_of = |voters: Vec<(u64, ExtendedBalance)>| Support {
+		total: voters.iter().map(|(_, w)| w).sum(),
+		voters,
+	};
+	let mut support_map: SupportMap<u64> = vec![
+		(10, support_of(vec![(1, 60), (2, 50)])),
+		(20, support_of(vec![(1, 40), (3, 30)])),
+	].into_iter().collect();
+	let mut stakes: BTreeMap<u64, ExtendedBalance> = vec![(1, 100), (2, 50), (3, 30)]
+		.into_iter()
+		.collect();
+
+	apply_slash(&mut support_map, &10, Perbill::from_percent(50), &mut stakes);
+
+	// 1 loses 30 out of 100, 2 loses 25 out of 50. 3 is not affected.
+	assert_eq!(stakes, vec![(1, 70), (2, 25), (3, 30)].into_iter().collect::<BTreeMap<_, _>>());
+
+	// 1 keeps 70% of all of its edges, including the one to 20.
+	assert_eq!(support_map.get(&10).unwrap(), &support_of(vec![(1, 42), (2, 25)]));
+	assert_eq!(support_map.get(&20).unwrap(), &support_of(vec![(1, 28), (3, 30)]));
+
+	// slashing a non-existing validator is a noop.
+	apply_slash(&mut support_map, &30, Perbill::from_percent(50), &mut stakes);
+	assert_eq!(stakes.get(&1), Some(&70));
+	assert_eq!(support_map.get(&10).unwrap(), &support_of(vec![(1, 42), (2, 25)]));
+}
+
+#[test]
+fn streaming_score_is_same_as_batch() {
+	let winners = (1..=50u64).collect::<Vec<_>>();
+	let assignments = (0..5_000u64)
+		.map(|i| {
+			let who = 1_000 + i;
+			let distribution = (0..(i % 4 + 1))
+				.map(|j| {
+					let target = (i * 7 + j * 13) % 50 + 1;
+					let weight = (i * 31 + j * 17) % 1_000 + 1;
+					(target, weight as ExtendedBalance)
+				})
+				.collect::<Vec<_>>();
+			StakedAssignment { who, distribution }
+		})
+		.collect::<Vec<_>>();
+
+	let support_map = build_support_map(&winners, &assignments).unwrap();
+	assert_eq!(
+		evaluate_support_streaming(&winners, assignments.clone().into_iter()).unwrap(),
+		evaluate_support(&support_map),
+	);
+
+	// winners without any support count as well.
+	let mut more_winners = winners.clone();
+	more_winners.push(51);
+	assert_eq!(
+		evaluate_support_streaming(&more_winners, assignments.clone().into_iter()).unwrap()[0],
+		0,
+	);
+
+	// edges to non-winners are an error.
+	assert_eq!(evaluate_support_streaming(&winners[1..], assignments.into_iter()), Err(1));
+}
+
+#[test]
+fn min_support_of_winners_ignores_losers() {
+	let mut support_map = SupportMap::<AccountId>::new();
+	support_map.insert(10, Support { total: 100, voters: vec![(1, 100)] });
+	support_map.insert(20, Support { total: 60, voters: vec![(2, 60)] });
+	support_map.insert(30, Support { total: 5, voters: vec![(3, 5)] });
+
+	assert_eq!(evaluate_support(&support_map)[0], 5);
+	assert_eq!(min_support_of_winners(&support_map, &[10, 20]), 60);
+	assert_eq!(min_support_of_winners(&support_map, &[10]), 100);
+
+	// a winner with no support at all.
+	assert_eq!(min_support_of_winners(&support_map, &[10, 40]), 0);
+	assert_eq!(min_support_of_winners(&support_map, &[]), ExtendedBalance::MAX);
+}
+
 mod assignment_convert_normalize {
 	use super::*;
 	#[test]
@@ -1016,6 +2649,84 @@ mod score {
 		}
 	}
 
+	#[test]
+	fn score_report_round_trip_works() {
+		let score: ElectionScore = [10, 20, 300];
+		let report = ScoreReport::from(score);
+		assert_eq!(
+			report,
+			ScoreReport { minimal_stake: 10, sum_stake: 20, sum_stake_squared: 300 },
+		);
+
+		let back: ElectionScore = report.into();
+		assert_eq!(back, score);
+	}
+
+	#[test]
+	fn full_mul_works() {
+		assert_eq!(U256::full_mul(3, 5), U256::from(15));
+		assert_eq!(U256::full_mul(1 << 64, 1 << 64), U256 { high: 1, low: 0 });
+		assert_eq!(
+			U256::full_mul(u128::MAX, u128::MAX),
+			U256 { high: u128::MAX - 1, low: 1 },
+		);
+	}
+
+	#[test]
+	fn exact_score_orders_beyond_u128() {
+		let x: ExtendedBalance = 1 << 100;
+		let support_of = |totals: &[ExtendedBalance]| totals
+			.iter()
+			.enumerate()
+			.map(|(i, t)| (i as u64, Support::<u64> { total: *t, voters: vec![] }))
+			.collect::<SupportMap<u64>>();
+
+		// same minimum and sum, sum of squares differs by 2 but is way more than `u128`.
+		let better = support_of(&[x, x + 2, x + 2]);
+		let worse = support_of(&[x, x + 1, x + 3]);
+
+		// the saturating path cannot tell them apart.
+		let saturated_better = evaluate_support(&better);
+		let saturated_worse = evaluate_support(&worse);
+		assert_eq!(saturated_better, saturated_worse);
+		assert_eq!(saturated_better[2], ExtendedBalance::MAX);
+		assert!(!is_score_better(saturated_better, saturated_worse, Perbill::zero()));
+		assert!(!is_score_better(saturated_worse, saturated_better, Perbill::zero()));
+
+		// the exact one can.
+		let exact_better = evaluate_support_exact(&better);
+		let exact_worse = evaluate_support_exact(&worse);
+		assert!(exact_better.2 < exact_worse.2);
+		assert!(is_exact_score_better(exact_better, exact_worse, Perbill::zero()));
+		assert!(!is_exact_score_better(exact_worse, exact_better, Perbill::zero()));
+
+		// but not if the difference is within epsilon.
+		assert!(!is_exact_score_better(exact_better, exact_worse, Perbill::from_percent(1)));
+	}
+
+	#[test]
+	fn exact_score_comparison_matches_when_not_saturated() {
+		let initial = [12488167277027543u128, 5559266368032409496, 118749283262079244270992278287436446];
+		let claim = [12488167277027543u128, 5559266368032409496, 118700736389524721358337889258988054];
+		let exact = |s: ElectionScore| (s[0], s[1], U256::from(s[2]));
+
+		for epsilon in [
+			Perbill::zero(),
+			Perbill::from_rational_approximation(1u32, 10_000),
+			Perbill::from_rational_approximation(5u32, 10_000),
+			Perbill::from_percent(1),
+		].iter().cloned() {
+			assert_eq!(
+				is_exact_score_better(exact(claim), exact(initial), epsilon),
+				is_score_better(claim, initial, epsilon),
+			);
+			assert_eq!(
+				is_exact_score_better(exact(initial), exact(claim), epsilon),
+				is_score_better(initial, claim, epsilon),
+			);
+		}
+	}
+
 	#[test]
 	fn score_comparison_large_value() {
 		// some random value taken from eras in kusama.